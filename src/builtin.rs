@@ -1,6 +1,8 @@
 #![allow(dead_code)]
 
-use std::io;
+pub(crate) mod lut;
+
+use std::{io, path::Path};
 
 use phf::phf_map;
 
@@ -30,6 +32,14 @@ impl BuiltinShader {
         _get(name.as_ref())
     }
 
+    pub fn all() -> impl Iterator<Item = BuiltinShader> {
+        BUILTIN_SHADERS.entries().map(|(key, value)| BuiltinShader {
+            // SAFETY: All keys are valid UTF-8 strings.
+            name: unsafe { std::str::from_utf8_unchecked(key) },
+            value,
+        })
+    }
+
     pub fn name(&self) -> &'static str {
         self.name
     }
@@ -38,6 +48,55 @@ impl BuiltinShader {
         self.value.is_template
     }
 
+    /// Makes relative paths given to path variables absolute, since the shader may be rendered
+    /// again from another directory (e.g. by `restore`). Paths starting with `~/` are kept as is.
+    pub fn absolutize_paths(&self, data: &mut TemplateDataMap) {
+        for (key, variable) in &self.value.metadata.variables {
+            let Variable::Path { .. } = variable else {
+                continue;
+            };
+            let Some(path) = data
+                .get_path(&[key])
+                .and_then(TemplateData::to_scalar_string)
+            else {
+                continue;
+            };
+            if path.is_empty() || path.starts_with("~/") || Path::new(&path).is_absolute() {
+                continue;
+            }
+            if let Ok(absolute) = std::path::absolute(&path) {
+                let absolute = TemplateData::Enum(absolute.to_string_lossy().into_owned());
+                data.insert_path(&[key], absolute);
+            }
+        }
+    }
+
+    /// Whether rendering depends on data computed at runtime, which only builtins can do
+    pub fn is_baked(&self) -> bool {
+        self.value.bake.is_some()
+    }
+
+    /// Describes the shader and its variables, as written alongside ejected shaders.
+    pub fn metadata_toml(&self) -> toml::Table {
+        let Metadata {
+            full_name,
+            description,
+            variables,
+        } = &self.value.metadata;
+        toml::Table::from_iter([
+            ("name".to_owned(), toml::Value::from(*full_name)),
+            ("description".to_owned(), toml::Value::from(*description)),
+            (
+                "variables".to_owned(),
+                toml::Value::Table(Variable::map_to_toml(variables)),
+            ),
+        ])
+    }
+
+    pub fn contents(&self) -> &'static str {
+        self.value.contents
+    }
+
     pub fn write<W: io::Write>(&self, wr: &mut W) -> io::Result<()> {
         wr.write_all(self.value.contents.as_bytes())
     }
@@ -60,9 +119,19 @@ impl BuiltinShader {
             self_data.merge_deep_force(data.clone());
             self_data
         };
+        let mut render_data = data.to_mustache_data();
+        if let Some(bake) = self.value.bake {
+            let baked = bake(&data).map_err(|source| RenderError::Bake {
+                name: self.name().to_owned(),
+                source,
+            })?;
+            if let mustache::Data::Map(map) = &mut render_data {
+                map.extend(baked);
+            }
+        }
 
         template
-            .render(out_file, &data)
+            .render_data(out_file, &render_data)
             .map_err(|source| RenderError::MustacheRender {
                 name: self.name().to_owned(),
                 source,
@@ -74,7 +143,7 @@ impl BuiltinShader {
         TemplateDataMap::from_iter(
             variables
                 .into_iter()
-                .map(|(k, v)| (k.to_string(), TemplateData::from(v))),
+                .filter_map(|(k, v)| Some((k.to_string(), v.default_value()?))),
         )
     }
 }
@@ -86,26 +155,82 @@ impl PartialEq for BuiltinShader {
 }
 impl Eq for BuiltinShader {}
 
-impl From<&Variable> for TemplateData {
-    fn from(value: &Variable) -> Self {
-        match value {
-            Variable::Float { default, .. } => TemplateData::Float(*default),
+impl Variable {
+    /// Returns the value used when none is given, or [`None`] if the variable has no default.
+    pub fn default_value(&self) -> Option<TemplateData> {
+        match self {
+            Variable::Float { default, .. } => Some(TemplateData::Float(*default)),
             Variable::Enum { default, .. } => {
-                TemplateData::Enum(default.to_owned().to_ascii_uppercase())
+                Some(TemplateData::Enum(default.to_owned().to_ascii_uppercase()))
             }
-            Variable::Map(map) => TemplateData::from_iter(
+            Variable::Bool { default, .. } => Some(TemplateData::Bool(*default)),
+            Variable::Path { .. } => None,
+            Variable::Map(map) => Some(TemplateData::from_iter(
                 map.into_iter()
-                    .map(|(k, v)| (k.to_string(), TemplateData::from(v))),
-            ),
+                    .filter_map(|(k, v)| Some((k.to_string(), v.default_value()?))),
+            )),
         }
     }
+
+    fn to_toml(&self) -> toml::Table {
+        let mut table = toml::Table::new();
+        let mut insert = |key: &str, value: toml::Value| table.insert(key.to_owned(), value);
+        match self {
+            Variable::Float {
+                description,
+                min,
+                max,
+                default,
+            } => {
+                insert("type", "float".into());
+                insert("description", (*description).into());
+                insert("min", (*min).into());
+                insert("max", (*max).into());
+                insert("default", (*default).into());
+            }
+            Variable::Enum {
+                description,
+                variants,
+                default,
+            } => {
+                insert("type", "enum".into());
+                insert("description", (*description).into());
+                insert("variants", variants.to_vec().into());
+                insert("default", (*default).into());
+            }
+            Variable::Bool {
+                description,
+                default,
+            } => {
+                insert("type", "bool".into());
+                insert("description", (*description).into());
+                insert("default", (*default).into());
+            }
+            Variable::Path { description } => {
+                insert("type", "path".into());
+                insert("description", (*description).into());
+            }
+            Variable::Map(map) => return Variable::map_to_toml(map),
+        }
+        table
+    }
+
+    fn map_to_toml(map: &phf::Map<&'static str, Variable>) -> toml::Table {
+        map.into_iter()
+            .map(|(k, v)| (k.to_string(), toml::Value::Table(v.to_toml())))
+            .collect()
+    }
 }
 
+/// Computes additional data for rendering that can't be expressed in a template
+type Bake = fn(&TemplateDataMap) -> Result<Vec<(String, mustache::Data)>, BakeError>;
+
 #[derive(Debug)]
 struct BuiltinShaderValue {
     contents: &'static str,
     is_template: bool,
     metadata: Metadata,
+    bake: Option<Bake>,
 }
 
 #[derive(Debug)]
@@ -115,8 +240,9 @@ struct Metadata {
     variables: phf::Map<&'static str, Variable>,
 }
 
+#[non_exhaustive]
 #[derive(Debug)]
-enum Variable {
+pub enum Variable {
     Float {
         description: &'static str,
         min: f64,
@@ -128,6 +254,13 @@ enum Variable {
         variants: &'static [&'static str],
         default: &'static str,
     },
+    Bool {
+        description: &'static str,
+        default: bool,
+    },
+    Path {
+        description: &'static str,
+    },
     Map(phf::Map<&'static str, Variable>),
 }
 
@@ -153,8 +286,89 @@ const BUILTIN_SHADERS: BuiltinShaders = phf_map! {
                     max: 1.0,
                     default: 1.0,
                 },
+                "method" => Variable::Enum {
+                    description: "\
+                        Method for converting the temperature to a color.\n\
+                        - \"polynomial\": Fast curve fit.\n\
+                        - \"planckian\": Bradford adaptation to the CIE 1931 Planckian locus, \
+                        which avoids hue shifts.\
+                    ",
+                    variants: &["polynomial", "planckian"],
+                    default: "polynomial",
+                },
+                "preserve_luminance" => Variable::Bool {
+                    description: "Keep the brightness of each pixel unchanged.",
+                    default: false,
+                },
+                "linear_light" => Variable::Bool {
+                    description: "Apply the filter to linear light instead of gamma-encoded sRGB.",
+                    default: false,
+                },
+            },
+        },
+        bake: None,
+    },
+    b"color-adjust" => BuiltinShaderValue {
+        contents: include_str!("shaders/color-adjust.glsl.mustache"),
+        is_template: true,
+        metadata: Metadata {
+            full_name: "Color Adjust",
+            description: "\
+                Adjust brightness, contrast, gamma, and saturation.\n\
+                Lower the brightness to dim a screen that is too bright at night.\
+            ",
+            variables: phf_map! {
+                "brightness" => Variable::Float {
+                    description: "Brightness multiplier. (Values below 1.0 dim the screen.)",
+                    min: 0.0,
+                    max: 2.0,
+                    default: 1.0,
+                },
+                "contrast" => Variable::Float {
+                    description: "Contrast multiplier, centered on mid-gray.",
+                    min: 0.0,
+                    max: 2.0,
+                    default: 1.0,
+                },
+                "gamma" => Variable::Map(phf_map! {
+                    "red" => Variable::Float {
+                        description: "Gamma correction (red).",
+                        min: 0.1,
+                        max: 10.0,
+                        default: 1.0,
+                    },
+                    "green" => Variable::Float {
+                        description: "Gamma correction (green).",
+                        min: 0.1,
+                        max: 10.0,
+                        default: 1.0,
+                    },
+                    "blue" => Variable::Float {
+                        description: "Gamma correction (blue).",
+                        min: 0.1,
+                        max: 10.0,
+                        default: 1.0,
+                    },
+                }),
+                "saturation" => Variable::Float {
+                    description: "Saturation multiplier. (0.0 is grayscale.)",
+                    min: 0.0,
+                    max: 2.0,
+                    default: 1.0,
+                },
+                "dither" => Variable::Enum {
+                    description: "\
+                        Ordered dithering applied after adjustment to avoid banding.\n\
+                        - \"none\": No dithering.\n\
+                        - \"bayer4\": 4x4 Bayer matrix.\n\
+                        - \"bayer8\": 8x8 Bayer matrix.\
+                    ",
+                    variants: &["none", "bayer4", "bayer8"],
+                    default: "none",
+                },
             },
         },
+        bake: None,
     },
     b"color-filter" => BuiltinShaderValue {
         contents: include_str!("shaders/color-filter.glsl.mustache"),
@@ -162,16 +376,27 @@ const BUILTIN_SHADERS: BuiltinShaders = phf_map! {
         metadata: Metadata {
             full_name: "Color Filter",
             description: "\
-                Adjust colors for color vision deficiencies.\n\
-                Supports protanopia (red-green), deuteranopia (green-red), and tritanopia (blue-yellow).\
+                Adjust colors for color vision deficiencies, or simulate them.\n\
+                Supports protanopia (red-green), deuteranopia (green-red), tritanopia (blue-yellow), \
+                and achromatopsia (total color blindness).\
             ",
             variables: phf_map! {
+                "mode" => Variable::Enum {
+                    description: "\
+                        Whether to correct or simulate the color vision deficiency.\n\
+                        - \"correct\": Shift colors so they can be told apart.\n\
+                        - \"simulate\": Show colors as they would be seen. (Machado et al. 2009)\
+                    ",
+                    variants: &["correct", "simulate"],
+                    default: "correct",
+                },
                 "type" => Variable::Enum {
                     description: "\
-                        Type of color correction.\n\
+                        Type of color vision deficiency.\n\
                         - \"protanopia\": Red-green color blindness.\n\
                         - \"deuteranopia\": Green-red color blindness.\n\
-                        - \"tritanopia\": Blue-yellow color blindness.\
+                        - \"tritanopia\": Blue-yellow color blindness.\n\
+                        - \"achromatopsia\": Total color blindness. (Only applies when mode = \"simulate\")\
                     ",
                     variants: &[
                         "protanopia",
@@ -183,17 +408,30 @@ const BUILTIN_SHADERS: BuiltinShaders = phf_map! {
                         "tritanopia",
                         "tritan",
                         "blueyellow",
+                        "achromatopsia",
+                        "achromat",
+                        "monochromacy",
                     ],
                     default: "protanopia",
                 },
                 "strength" => Variable::Float {
-                    description: "Strength of the effect.",
+                    description: "Strength of the effect. (Only applies when mode = \"correct\")",
                     min: 0.0,
                     max: 1.0,
                     default: 0.2,
                 },
+                "severity" => Variable::Float {
+                    description: "\
+                        Severity of the simulated deficiency, from none (0.0) to complete (1.0).\n\
+                        (Only applies when mode = \"simulate\")\
+                    ",
+                    min: 0.0,
+                    max: 1.0,
+                    default: 1.0,
+                },
             },
         },
+        bake: None,
     },
     b"grayscale" => BuiltinShaderValue {
         contents: include_str!("shaders/grayscale.glsl.mustache"),
@@ -224,15 +462,76 @@ const BUILTIN_SHADERS: BuiltinShaders = phf_map! {
                 },
             },
         },
+        bake: None,
     },
     b"invert-colors" => BuiltinShaderValue {
-        contents: include_str!("shaders/invert-colors.glsl"),
-        is_template: false,
+        contents: include_str!("shaders/invert-colors.glsl.mustache"),
+        is_template: true,
         metadata: Metadata {
             full_name: "Invert Colors",
-            description: "Invert colors so text and content stand out.",
-            variables: phf_map! {},
+            description: "\
+                Invert colors so text and content stand out.\n\
+                Inverting only the lightness keeps hues intact, so photos and syntax highlighting \
+                stay legible.\
+            ",
+            variables: phf_map! {
+                "type" => Variable::Enum {
+                    description: "\
+                        Type of inversion.\n\
+                        - \"rgb\": Invert each color channel.\n\
+                        - \"lightness\": Invert the lightness (OKLab) and keep hues.\n\
+                        - \"smart\": Invert the lightness of content brighter than the threshold.\
+                    ",
+                    variants: &["rgb", "lightness", "smart"],
+                    default: "rgb",
+                },
+                "strength" => Variable::Float {
+                    description: "Strength of the effect.",
+                    min: 0.0,
+                    max: 1.0,
+                    default: 1.0,
+                },
+                "threshold" => Variable::Float {
+                    description: "Lightness above which content is inverted with type 'smart'.",
+                    min: 0.0,
+                    max: 1.0,
+                    default: 0.5,
+                },
+            },
         },
+        bake: None,
+    },
+    b"lut" => BuiltinShaderValue {
+        contents: include_str!("shaders/lut.glsl.mustache"),
+        is_template: true,
+        metadata: Metadata {
+            full_name: "3D LUT",
+            description: "\
+                Apply a 3D lookup table in the Adobe/Resolve .cube format.\n\
+                The table is resampled and embedded into the shader when it is turned on.\
+            ",
+            variables: phf_map! {
+                "lut" => Variable::Path {
+                    description: "Path to a .cube file. (A leading \"~/\" refers to your home directory.)",
+                },
+                "size" => Variable::Float {
+                    description: "\
+                        Number of table entries per axis after resampling.\n\
+                        Larger tables are more accurate, but take longer to compile.\
+                    ",
+                    min: lut::MIN_SIZE as f64,
+                    max: lut::MAX_BAKED_SIZE as f64,
+                    default: 17.0,
+                },
+                "strength" => Variable::Float {
+                    description: "Strength of the effect.",
+                    min: 0.0,
+                    max: 1.0,
+                    default: 1.0,
+                },
+            },
+        },
+        bake: Some(|data| Ok(lut::bake(data)?)),
     },
     b"vibrance" => BuiltinShaderValue {
         contents: include_str!("shaders/vibrance.glsl.mustache"),
@@ -269,6 +568,7 @@ const BUILTIN_SHADERS: BuiltinShaders = phf_map! {
                 },
             },
         },
+        bake: None,
     },
 };
 
@@ -285,6 +585,15 @@ pub enum RenderError {
         name: String,
         source: mustache::Error,
     },
+    #[error("baking data for {name}")]
+    Bake { name: String, source: BakeError },
+}
+
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum BakeError {
+    #[error(transparent)]
+    Lut(#[from] lut::Error),
 }
 
 #[cfg(test)]
@@ -301,4 +610,104 @@ mod tests {
         assert_ne!(entry_1, entry_3);
         assert_ne!(entry_2, entry_3);
     }
+
+    #[test]
+    fn render_builtin_shaders_with_defaults() {
+        for name in BUILTIN_SHADERS.keys() {
+            let builtin_shader = BuiltinShader::get(name).unwrap();
+            if !builtin_shader.is_template() || builtin_shader.value.bake.is_some() {
+                continue;
+            }
+
+            let mut out = Vec::new();
+            builtin_shader
+                .render(&mut out, &TemplateDataMap::new())
+                .unwrap_or_else(|err| panic!("rendering {}: {err}", builtin_shader.name()));
+            let out = String::from_utf8(out).unwrap();
+            assert!(
+                !out.contains("float()") && !out.contains("= ;"),
+                "{} has a variable without a default:\n{out}",
+                builtin_shader.name()
+            );
+        }
+    }
+
+    #[test]
+    fn metadata_toml_defaults_match_builtin() {
+        for builtin_shader in BuiltinShader::all() {
+            let table = builtin_shader.metadata_toml();
+            let Some(toml::Value::Table(variables)) = table.get("variables") else {
+                panic!("{} has no variables table", builtin_shader.name());
+            };
+            let defaults = crate::metadata::defaults(variables);
+
+            assert_eq!(
+                defaults.to_mustache_data(),
+                builtin_shader.data().to_mustache_data(),
+                "{}",
+                builtin_shader.name()
+            );
+        }
+    }
+
+    /// Reads `const mat3 <name> = mat3(...)` from a shader, in the order the rows are written
+    fn glsl_mat3(contents: &str, name: &str) -> [[f64; 3]; 3] {
+        let start = contents
+            .find(&format!("const mat3 {name} = mat3("))
+            .unwrap_or_else(|| panic!("{name} not found"));
+        let args = &contents[start..];
+        let args = &args[args.find('(').unwrap() + 1..args.find(')').unwrap()];
+        let values: Vec<f64> = args.split(',').map(|v| v.trim().parse().unwrap()).collect();
+        let mut mat = [[0.0; 3]; 3];
+        for (i, value) in values.into_iter().enumerate() {
+            mat[i / 3][i % 3] = value;
+        }
+        mat
+    }
+
+    /// Same as `vector * matrix` in GLSL, where each written row is a column
+    fn mul(vector: [f64; 3], mat: [[f64; 3]; 3]) -> [f64; 3] {
+        mat.map(|row| (0..3).map(|i| vector[i] * row[i]).sum())
+    }
+
+    #[test]
+    fn invert_colors_oklab_round_trip() {
+        let contents = BuiltinShader::get("invert-colors").unwrap().contents();
+        let [
+            linear_srgb_to_lms,
+            lms_to_oklab,
+            oklab_to_lms,
+            lms_to_linear_srgb,
+        ] = [
+            "LinearSrgbToLms",
+            "LmsToOklab",
+            "OklabToLms",
+            "LmsToLinearSrgb",
+        ]
+        .map(|name| glsl_mat3(contents, name));
+
+        for color in [[1.0; 3], [0.0; 3]] {
+            let lab = mul(mul(color, linear_srgb_to_lms).map(f64::cbrt), lms_to_oklab);
+            assert!(
+                (lab[0] - color[0]).abs() < 1e-4,
+                "lightness of {color:?} is {lab:?}"
+            );
+            assert!(
+                lab[1].abs() < 1e-4 && lab[2].abs() < 1e-4,
+                "{color:?} is {lab:?}"
+            );
+
+            // Without the clamp in the shader, which would hide overshooting
+            let round_trip = mul(
+                mul(lab, oklab_to_lms).map(|v| v * v * v),
+                lms_to_linear_srgb,
+            );
+            for (value, expected) in round_trip.into_iter().zip(color) {
+                assert!(
+                    (value - expected).abs() < 1e-4,
+                    "{color:?} became {round_trip:?}"
+                );
+            }
+        }
+    }
 }
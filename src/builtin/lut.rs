@@ -0,0 +1,543 @@
+//! Parsing and baking of 3D lookup tables in the Adobe/Resolve `.cube` format
+//!
+//! Screen shaders can't sample external textures, so the table is resampled to a small size and
+//! embedded into the generated GLSL as a constant array.
+
+use std::{
+    collections::HashMap,
+    fmt::Write as _,
+    fs, io,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use directories::BaseDirs;
+use tracing::debug;
+
+use crate::template::{TemplateData, TemplateDataMap};
+
+pub const MIN_SIZE: usize = 2;
+pub const MAX_SIZE: usize = 256;
+/// Largest table that is embedded into a shader, since Hyprland has to compile every entry
+pub const MAX_BAKED_SIZE: usize = 32;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Lut3d {
+    size: usize,
+    domain_min: [f64; 3],
+    domain_max: [f64; 3],
+    /// Entries in `.cube` order, i.e. with the red index changing fastest
+    table: Vec<[f64; 3]>,
+}
+
+impl Lut3d {
+    pub fn read(path: &Path) -> Result<Self, Error> {
+        let contents = fs::read_to_string(path).map_err(|source| Error::Read {
+            path: path.to_owned(),
+            source,
+        })?;
+        contents.parse().map_err(|source| Error::Parse {
+            path: path.to_owned(),
+            source,
+        })
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Resamples the table to `size` entries per axis using trilinear interpolation.
+    ///
+    /// Tables are never upsampled; if `size` is not smaller than the current size, the table is
+    /// returned unchanged.
+    pub fn downsample(&self, size: usize) -> Self {
+        let size = size.max(MIN_SIZE);
+        if size >= self.size {
+            return self.clone();
+        }
+
+        let scale = (self.size - 1) as f64 / (size - 1) as f64;
+        let mut table = Vec::with_capacity(size.pow(3));
+        for b in 0..size {
+            for g in 0..size {
+                for r in 0..size {
+                    table.push(self.sample([r as f64 * scale, g as f64 * scale, b as f64 * scale]));
+                }
+            }
+        }
+
+        Self {
+            size,
+            domain_min: self.domain_min,
+            domain_max: self.domain_max,
+            table,
+        }
+    }
+
+    /// Trilinearly interpolates the table at fractional `(r, g, b)` indices.
+    fn sample(&self, position: [f64; 3]) -> [f64; 3] {
+        let max = (self.size - 1) as f64;
+        let position = position.map(|p| p.clamp(0.0, max));
+        let lower = position.map(|p| p.floor() as usize);
+        let upper = lower.map(|i| (i + 1).min(self.size - 1));
+        let fract = [0, 1, 2].map(|i| position[i] - lower[i] as f64);
+
+        let at = |r: usize, g: usize, b: usize| self.table[r + self.size * (g + self.size * b)];
+        let mix = |a: [f64; 3], b: [f64; 3], t: f64| [0, 1, 2].map(|i| a[i] + (b[i] - a[i]) * t);
+
+        let (r0, g0, b0) = (lower[0], lower[1], lower[2]);
+        let (r1, g1, b1) = (upper[0], upper[1], upper[2]);
+
+        let c00 = mix(at(r0, g0, b0), at(r1, g0, b0), fract[0]);
+        let c10 = mix(at(r0, g1, b0), at(r1, g1, b0), fract[0]);
+        let c01 = mix(at(r0, g0, b1), at(r1, g0, b1), fract[0]);
+        let c11 = mix(at(r0, g1, b1), at(r1, g1, b1), fract[0]);
+
+        mix(mix(c00, c10, fract[1]), mix(c01, c11, fract[1]), fract[2])
+    }
+
+    /// Converts into data for rendering the `lut` template.
+    pub fn to_mustache_data(&self) -> mustache::Data {
+        fn vec3(v: &[f64; 3]) -> String {
+            format!("vec3({:.6}, {:.6}, {:.6})", v[0], v[1], v[2])
+        }
+
+        let table = self.table.iter().fold(String::new(), |mut s, entry| {
+            if !s.is_empty() {
+                s.push_str(",\n");
+            }
+            let _ = write!(s, "    {}", vec3(entry));
+            s
+        });
+
+        mustache::Data::Map(HashMap::from([
+            (
+                "size".to_owned(),
+                mustache::Data::String(self.size.to_string()),
+            ),
+            (
+                "domain_min".to_owned(),
+                mustache::Data::String(vec3(&self.domain_min)),
+            ),
+            (
+                "domain_max".to_owned(),
+                mustache::Data::String(vec3(&self.domain_max)),
+            ),
+            ("table".to_owned(), mustache::Data::String(table)),
+        ]))
+    }
+}
+
+impl FromStr for Lut3d {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut size = None;
+        let mut domain_min = [0.0; 3];
+        let mut domain_max = [1.0; 3];
+        let mut table = Vec::new();
+        let mut line_number = 0;
+
+        for (i, line) in s.lines().enumerate() {
+            line_number = i + 1;
+            let err = |kind| ParseError {
+                line: line_number,
+                kind,
+            };
+
+            let line = line.split('#').next().unwrap_or_default().trim();
+            let mut words = line.split_whitespace();
+            let Some(keyword) = words.next() else {
+                continue;
+            };
+            let rest: Vec<&str> = words.collect();
+
+            if keyword
+                .chars()
+                .next()
+                .is_some_and(|c| c.is_ascii_alphabetic())
+            {
+                if !table.is_empty() {
+                    return Err(err(ParseErrorKind::KeywordAfterData(keyword.to_owned())));
+                }
+                match keyword {
+                    "LUT_3D_SIZE" => {
+                        let value = one_value(&rest).map_err(err)?;
+                        let parsed = value
+                            .parse::<usize>()
+                            .map_err(|_| err(ParseErrorKind::InvalidSize(value.to_owned())))?;
+                        if !(MIN_SIZE..=MAX_SIZE).contains(&parsed) {
+                            return Err(err(ParseErrorKind::SizeOutOfRange(parsed)));
+                        }
+                        size = Some(parsed);
+                    }
+                    "LUT_1D_SIZE" => return Err(err(ParseErrorKind::Unsupported1d)),
+                    "DOMAIN_MIN" => domain_min = three_values(&rest).map_err(err)?,
+                    "DOMAIN_MAX" => domain_max = three_values(&rest).map_err(err)?,
+                    "LUT_3D_INPUT_RANGE" => {
+                        let [min, max] = two_values(&rest).map_err(err)?;
+                        domain_min = [min; 3];
+                        domain_max = [max; 3];
+                    }
+                    _ => debug!("Ignoring keyword {keyword:?} on line {line_number}"),
+                }
+                continue;
+            }
+
+            let Some(size) = size else {
+                return Err(err(ParseErrorKind::DataBeforeSize));
+            };
+            if table.len() == size.pow(3) {
+                return Err(err(ParseErrorKind::TooManyEntries(size.pow(3))));
+            }
+            let mut values = vec![keyword];
+            values.extend(rest);
+            table.push(three_values(&values).map_err(err)?);
+        }
+
+        let size = size.ok_or(ParseError {
+            line: line_number,
+            kind: ParseErrorKind::MissingSize,
+        })?;
+        if table.len() != size.pow(3) {
+            return Err(ParseError {
+                line: line_number,
+                kind: ParseErrorKind::TooFewEntries {
+                    expected: size.pow(3),
+                    found: table.len(),
+                },
+            });
+        }
+        if (0..3).any(|i| domain_min[i] >= domain_max[i]) {
+            return Err(ParseError {
+                line: line_number,
+                kind: ParseErrorKind::InvalidDomain,
+            });
+        }
+
+        Ok(Self {
+            size,
+            domain_min,
+            domain_max,
+            table,
+        })
+    }
+}
+
+fn parse_values<const N: usize>(values: &[&str]) -> Result<[f64; N], ParseErrorKind> {
+    if values.len() != N {
+        return Err(ParseErrorKind::WrongValueCount {
+            expected: N,
+            found: values.len(),
+        });
+    }
+    let mut ret = [0.0; N];
+    for (r, value) in ret.iter_mut().zip(values) {
+        *r = value
+            .parse::<f64>()
+            .ok()
+            .filter(|v| v.is_finite())
+            .ok_or_else(|| ParseErrorKind::InvalidNumber((*value).to_owned()))?;
+    }
+    Ok(ret)
+}
+
+fn one_value<'a>(values: &[&'a str]) -> Result<&'a str, ParseErrorKind> {
+    match values {
+        [value] => Ok(value),
+        _ => Err(ParseErrorKind::WrongValueCount {
+            expected: 1,
+            found: values.len(),
+        }),
+    }
+}
+
+fn two_values(values: &[&str]) -> Result<[f64; 2], ParseErrorKind> {
+    parse_values(values)
+}
+
+fn three_values(values: &[&str]) -> Result<[f64; 3], ParseErrorKind> {
+    parse_values(values)
+}
+
+/// Reads the `.cube` file named by the `lut` variable and bakes it at the size given by the
+/// `size` variable.
+pub fn bake(data: &TemplateDataMap) -> Result<Vec<(String, mustache::Data)>, Error> {
+    let path = match data
+        .get_path(&["lut"])
+        .and_then(TemplateData::to_scalar_string)
+    {
+        Some(path) if !path.is_empty() => expand_tilde(&path),
+        Some(_) | None => return Err(Error::MissingFile),
+    };
+    let size = match data.get_path(&["size"]) {
+        Some(TemplateData::Float(size)) if size.is_finite() => {
+            size.round().clamp(MIN_SIZE as f64, MAX_BAKED_SIZE as f64) as usize
+        }
+        _ => return Err(Error::InvalidSize),
+    };
+
+    let lut = Lut3d::read(&path)?.downsample(size);
+    debug!("Baked {path:?} into a {0}x{0}x{0} table", lut.size());
+
+    Ok(vec![("baked".to_owned(), lut.to_mustache_data())])
+}
+
+fn expand_tilde(path: &str) -> PathBuf {
+    match path.strip_prefix("~/") {
+        Some(rest) => match BaseDirs::new() {
+            Some(dirs) => dirs.home_dir().join(rest),
+            None => PathBuf::from(path),
+        },
+        None => PathBuf::from(path),
+    }
+}
+
+#[non_exhaustive]
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("no LUT file given (use --var lut=PATH)")]
+    MissingFile,
+    #[error("LUT size must be a number")]
+    InvalidSize,
+    #[error("reading LUT file at {}", path.display())]
+    Read { path: PathBuf, source: io::Error },
+    #[error("parsing LUT file at {}", path.display())]
+    Parse { path: PathBuf, source: ParseError },
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("line {line}: {kind}")]
+pub struct ParseError {
+    line: usize,
+    kind: ParseErrorKind,
+}
+
+#[non_exhaustive]
+#[derive(Debug, thiserror::Error)]
+pub enum ParseErrorKind {
+    #[error("missing LUT_3D_SIZE")]
+    MissingSize,
+    #[error("invalid LUT_3D_SIZE {0:?}")]
+    InvalidSize(String),
+    #[error("LUT_3D_SIZE must be between {MIN_SIZE} and {MAX_SIZE}, found {0}")]
+    SizeOutOfRange(usize),
+    #[error("1D LUTs are not supported")]
+    Unsupported1d,
+    #[error("table data must come after LUT_3D_SIZE")]
+    DataBeforeSize,
+    #[error("keyword {0} must come before table data")]
+    KeywordAfterData(String),
+    #[error("invalid number {0:?}")]
+    InvalidNumber(String),
+    #[error("expected {expected} values, found {found}")]
+    WrongValueCount { expected: usize, found: usize },
+    #[error("expected {expected} table entries, found {found}")]
+    TooFewEntries { expected: usize, found: usize },
+    #[error("too many table entries (expected {0})")]
+    TooManyEntries(usize),
+    #[error("DOMAIN_MIN must be less than DOMAIN_MAX")]
+    InvalidDomain,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const IDENTITY_2: &str = "\
+        # Created by hand\n\
+        TITLE \"identity\"\n\
+        LUT_3D_SIZE 2\n\
+        \n\
+        0 0 0\n\
+        1 0 0\n\
+        0 1 0\n\
+        1 1 0\n\
+        0 0 1\n\
+        1 0 1\n\
+        0 1 1\n\
+        1 1 1\n\
+    ";
+
+    fn identity(size: usize) -> Lut3d {
+        let max = (size - 1) as f64;
+        let mut table = Vec::new();
+        for b in 0..size {
+            for g in 0..size {
+                for r in 0..size {
+                    table.push([r as f64 / max, g as f64 / max, b as f64 / max]);
+                }
+            }
+        }
+        Lut3d {
+            size,
+            domain_min: [0.0; 3],
+            domain_max: [1.0; 3],
+            table,
+        }
+    }
+
+    fn parse_err(s: &str) -> ParseError {
+        s.parse::<Lut3d>().unwrap_err()
+    }
+
+    #[test]
+    fn parse_valid() {
+        let lut: Lut3d = IDENTITY_2.parse().unwrap();
+        assert_eq!(lut, identity(2));
+    }
+
+    #[test]
+    fn parse_domain() {
+        let lut: Lut3d = IDENTITY_2
+            .replace(
+                "LUT_3D_SIZE 2",
+                "LUT_3D_SIZE 2\nDOMAIN_MIN 0 0 0.5\nDOMAIN_MAX 1 2 1",
+            )
+            .parse()
+            .unwrap();
+        assert_eq!(lut.domain_min, [0.0, 0.0, 0.5]);
+        assert_eq!(lut.domain_max, [1.0, 2.0, 1.0]);
+    }
+
+    #[test]
+    fn parse_invalid() {
+        let err = parse_err("0 0 0\n");
+        assert_eq!(err.line, 1);
+        assert!(matches!(err.kind, ParseErrorKind::DataBeforeSize));
+
+        let err = parse_err("LUT_1D_SIZE 4\n");
+        assert!(matches!(err.kind, ParseErrorKind::Unsupported1d));
+
+        let err = parse_err("LUT_3D_SIZE 1\n");
+        assert!(matches!(err.kind, ParseErrorKind::SizeOutOfRange(1)));
+
+        let err = parse_err("LUT_3D_SIZE two\n");
+        assert!(matches!(err.kind, ParseErrorKind::InvalidSize(_)));
+
+        let err = parse_err("TITLE \"empty\"\n");
+        assert!(matches!(err.kind, ParseErrorKind::MissingSize));
+
+        let err = parse_err(&IDENTITY_2.replace("1 1 0", "1 1"));
+        assert_eq!(err.line, 8);
+        assert!(matches!(
+            err.kind,
+            ParseErrorKind::WrongValueCount {
+                expected: 3,
+                found: 2
+            }
+        ));
+
+        let err = parse_err(&IDENTITY_2.replace("1 1 0", "1 one 0"));
+        assert!(matches!(err.kind, ParseErrorKind::InvalidNumber(_)));
+
+        let err = parse_err(&IDENTITY_2.replace("1 1 1\n", ""));
+        assert!(matches!(
+            err.kind,
+            ParseErrorKind::TooFewEntries {
+                expected: 8,
+                found: 7
+            }
+        ));
+
+        let err = parse_err(&format!("{IDENTITY_2}1 1 1\n"));
+        assert!(matches!(err.kind, ParseErrorKind::TooManyEntries(8)));
+
+        let err = parse_err(&format!("{IDENTITY_2}DOMAIN_MIN 0 0 0\n"));
+        assert!(matches!(err.kind, ParseErrorKind::KeywordAfterData(_)));
+
+        let err =
+            parse_err(&IDENTITY_2.replace("LUT_3D_SIZE 2", "LUT_3D_SIZE 2\nDOMAIN_MIN 1 1 1"));
+        assert!(matches!(err.kind, ParseErrorKind::InvalidDomain));
+    }
+
+    #[test]
+    fn downsample() {
+        let lut = identity(17).downsample(5);
+        assert_eq!(lut.size, 5);
+        for (actual, expected) in lut.table.iter().zip(&identity(5).table) {
+            for i in 0..3 {
+                assert!((actual[i] - expected[i]).abs() < 1e-9);
+            }
+        }
+
+        assert_eq!(identity(5).downsample(17), identity(5));
+    }
+
+    #[test]
+    fn to_mustache_data() {
+        let mustache::Data::Map(data) = identity(2).to_mustache_data() else {
+            panic!("expected map");
+        };
+        let Some(mustache::Data::String(table)) = data.get("table") else {
+            panic!("expected table");
+        };
+        assert_eq!(table.lines().count(), 8);
+        assert_eq!(
+            table.lines().nth(1),
+            Some("    vec3(1.000000, 0.000000, 0.000000),")
+        );
+    }
+
+    fn identity_file(size: usize) -> tempfile::NamedTempFile {
+        let mut cube = format!("LUT_3D_SIZE {size}\n");
+        for [r, g, b] in &identity(size).table {
+            writeln!(cube, "{r} {g} {b}").unwrap();
+        }
+        let file = tempfile::NamedTempFile::new().unwrap();
+        fs::write(file.path(), cube).unwrap();
+        file
+    }
+
+    fn bake_file(file: &tempfile::NamedTempFile, size: usize) -> HashMap<String, mustache::Data> {
+        let data = TemplateDataMap::from([
+            (
+                "lut".to_owned(),
+                TemplateData::Enum(file.path().to_string_lossy().into_owned()),
+            ),
+            ("size".to_owned(), TemplateData::Float(size as f64)),
+        ]);
+        let mut baked = bake(&data).unwrap();
+        let Some((_, mustache::Data::Map(baked))) = baked.pop() else {
+            panic!("expected one map");
+        };
+        baked
+    }
+
+    #[test]
+    fn bake_clamps_size() {
+        let baked = bake_file(&identity_file(MAX_BAKED_SIZE + 1), MAX_SIZE);
+        let Some(mustache::Data::String(size)) = baked.get("size") else {
+            panic!("expected size");
+        };
+        assert_eq!(*size, MAX_BAKED_SIZE.to_string());
+    }
+
+    #[test]
+    fn bake_max_size() {
+        // Hyprland compiles the shader whenever it is turned on, which gets slow for large tables
+        let baked = bake_file(&identity_file(MAX_BAKED_SIZE), MAX_BAKED_SIZE);
+        let Some(mustache::Data::String(table)) = baked.get("table") else {
+            panic!("expected table");
+        };
+        assert_eq!(table.lines().count(), MAX_BAKED_SIZE.pow(3));
+        assert!(table.len() < 2 * 1024 * 1024, "{} bytes", table.len());
+    }
+
+    #[test]
+    fn bake_scalar_path() {
+        for (lut, path) in [
+            (TemplateData::Float(1.5), "1.5"),
+            (TemplateData::Bool(true), "true"),
+        ] {
+            let data = TemplateDataMap::from([
+                ("lut".to_owned(), lut),
+                ("size".to_owned(), TemplateData::Float(17.0)),
+            ]);
+            match bake(&data) {
+                Err(Error::Read { path: read, .. }) => assert_eq!(read, Path::new(path)),
+                other => panic!("expected {path:?} to be read, got {other:?}"),
+            }
+        }
+    }
+}
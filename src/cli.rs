@@ -1,20 +1,37 @@
 mod arg {
+    pub(crate) mod complete;
     pub(crate) mod help;
     pub(crate) mod var;
 }
 mod instrumentation;
 mod subcommand;
 
-use std::{path::PathBuf, process::ExitCode};
+use std::{
+    env,
+    ffi::OsString,
+    fs,
+    io::{self, Write},
+    path::{self, Path, PathBuf},
+    process::ExitCode,
+    time::SystemTime,
+};
 
 use clap::Parser;
+use clap_complete::ArgValueCandidates;
+use tracing::debug;
 
 use crate::{
     config::{Config, ConfigReadError},
-    constants::{HYPRLAND_CONFIG_DIR, HYPRSHADE_CONFIG_DIR, HYPRSHADE_CONFIG_FILE_ENV},
+    constants::HYPRSHADE_CONFIG_FILE_ENV,
+    control::{self, Request, Response},
+    gc, hyprctl,
+    index::ShaderIndex,
+    instance,
+    lock::RuntimeLock,
+    timer,
 };
 
-use self::{instrumentation::Instrumentation, subcommand::HyprshadeSubcommand};
+use self::{arg::complete, instrumentation::Instrumentation, subcommand::HyprshadeSubcommand};
 
 pub trait CommandExecute {
     fn execute(self, config: Option<&Config>) -> eyre::Result<ExitCode>;
@@ -30,43 +47,193 @@ pub struct Cli {
     #[arg(long, env = HYPRSHADE_CONFIG_FILE_ENV, global = true)]
     config: Option<PathBuf>,
 
+    /// Walk shader directories instead of using the cached shader index
+    #[arg(long, global = true)]
+    no_cache: bool,
+
+    /// Signature of the Hyprland instance to act on, instead of the one hyprshade is running in
+    #[arg(long, value_name = "SIGNATURE", global = true, value_parser = instance::parse_signature, add = ArgValueCandidates::new(complete::instance))]
+    instance: Option<String>,
+
     #[command(subcommand)]
     command: HyprshadeSubcommand,
 }
 
 impl Cli {
     pub fn config(&self) -> Result<Option<Config>, ConfigReadError> {
-        if let Some(path) = &self.config {
-            return Some(Config::read(path)).transpose();
+        read_config(self.config.as_deref())
+    }
+
+    /// Makes the instance given to `--instance` the one every subcommand acts on.
+    pub fn select_instance(&self) {
+        if let Some(signature) = &self.instance {
+            instance::select(signature.clone());
+        }
+    }
+
+    /// Global options to run hyprshade with again later, e.g. once `inhibit --for` expires.
+    fn global_args(&self) -> Vec<OsString> {
+        let mut args = Vec::new();
+        if let Some(config) = &self.config {
+            // Scheduled commands don't run in the current directory
+            let config = path::absolute(config).unwrap_or_else(|_| config.clone());
+            args.extend([OsString::from("--config"), config.into_os_string()]);
+        }
+        if self.no_cache {
+            args.push(OsString::from("--no-cache"));
+        }
+        args
+    }
+
+    /// Has `hyprshade serve` run the subcommand, or returns [`None`] if it isn't running or the
+    /// subcommand must run in this process.
+    pub fn forward(&self) -> eyre::Result<Option<ExitCode>> {
+        if !self.command.is_forwarded() || self.config.is_some() || self.no_cache {
+            return Ok(None);
         }
+        let Ok(args) = env::args_os()
+            .skip(1)
+            .map(OsString::into_string)
+            .collect::<Result<Vec<_>, _>>()
+        else {
+            debug!("Not forwarding arguments that aren't valid UTF-8");
+            return Ok(None);
+        };
 
-        for path in &[
-            HYPRLAND_CONFIG_DIR.to_owned().join("hyprshade.toml"),
-            HYPRSHADE_CONFIG_DIR.to_owned().join("config.toml"),
-        ] {
-            match Config::read(path) {
-                Ok(config) => return Ok(Some(config)),
-                Err(ConfigReadError::Io { .. }) => continue,
-                Err(err @ ConfigReadError::Parse { .. }) => return Err(err),
+        let request = Request {
+            args,
+            cwd: env::current_dir().ok(),
+            env: Some(Request::current_env()),
+        };
+        match control::send(&request)? {
+            None => Ok(None),
+            Some(Response::Declined { message }) => {
+                debug!("Running in this process: {message}");
+                Ok(None)
+            }
+            Some(Response::Ok { output, log }) => {
+                eprint!("{log}");
+                io::stdout().write_all(output.as_bytes())?;
+                Ok(Some(ExitCode::SUCCESS))
+            }
+            // As returning the error from main would print it
+            Some(Response::Error { report, log, .. }) => {
+                eprint!("{log}");
+                eprintln!("Error: {report}");
+                Ok(Some(ExitCode::FAILURE))
             }
         }
+    }
+}
+
+/// Reads the config file at `path`, or else the first one found in the default locations.
+fn read_config(path: Option<&Path>) -> Result<Option<Config>, ConfigReadError> {
+    if let Some(path) = path {
+        return Some(Config::read(path)).transpose();
+    }
+
+    Config::find()
+}
 
-        Ok(None)
+/// Configuration of a subcommand that keeps running, like `serve`, which is read again when the
+/// file changes
+#[derive(Debug)]
+pub struct LiveConfig {
+    config: Option<Config>,
+    /// When the configuration file was modified as of reading it
+    modified: Option<SystemTime>,
+}
+
+impl LiveConfig {
+    pub fn new(config: Option<Config>) -> Self {
+        let modified = config.as_ref().and_then(|config| mtime(config.path()));
+        LiveConfig { config, modified }
+    }
+
+    pub fn get(&self) -> Option<&Config> {
+        self.config.as_ref()
+    }
+
+    /// Reads the configuration file again if it changed, or looks for one if there was none.
+    pub fn reload(&mut self) -> Result<Option<&Config>, ConfigReadError> {
+        let modified = self.config.as_ref().and_then(|config| mtime(config.path()));
+        let (config, modified) = match &self.config {
+            Some(_) if modified == self.modified => return Ok(self.config.as_ref()),
+            Some(config) if modified.is_some() => (Some(Config::read(config.path())?), modified),
+            _ => {
+                let config = Config::find()?;
+                let modified = config.as_ref().and_then(|config| mtime(config.path()));
+                (config, modified)
+            }
+        };
+
+        if let Some(config) = &config {
+            debug!("Read configuration file {:?}", config.path());
+        }
+        self.config = config;
+        self.modified = modified;
+        Ok(self.config.as_ref())
     }
 }
 
+fn mtime(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
 impl CommandExecute for Cli {
     #[tracing::instrument(level = "trace", skip_all)]
     fn execute(self, config: Option<&Config>) -> eyre::Result<ExitCode> {
-        match self.command {
+        if self.no_cache {
+            ShaderIndex::disable_cache();
+        }
+        if let Some(config) = config {
+            config.add_shader_dirs();
+        }
+        timer::set_global_args(self.global_args());
+
+        let changes_shader = self.command.changes_shader();
+        let _lock = changes_shader.then(RuntimeLock::acquire).transpose()?;
+
+        let exit_code = match self.command {
+            HyprshadeSubcommand::Adjust(adjust) => adjust.execute(config),
             HyprshadeSubcommand::Auto(auto) => auto.execute(config),
+            HyprshadeSubcommand::Completions(completions) => completions.execute(config),
             HyprshadeSubcommand::Current(current) => current.execute(config),
+            #[cfg(feature = "dbus")]
+            HyprshadeSubcommand::DbusService(dbus_service) => dbus_service.execute(config),
+            HyprshadeSubcommand::Gc(gc) => gc.execute(config),
+            HyprshadeSubcommand::Inhibit(inhibit) => inhibit.execute(config),
             HyprshadeSubcommand::Install(install) => install.execute(config),
+            HyprshadeSubcommand::Instances(instances) => instances.execute(config),
             HyprshadeSubcommand::Ls(ls) => ls.execute(config),
             HyprshadeSubcommand::Off(off) => off.execute(config),
             HyprshadeSubcommand::On(on) => on.execute(config),
+            HyprshadeSubcommand::Restore(restore) => restore.execute(config),
+            HyprshadeSubcommand::Revert(revert) => revert.execute(config),
+            HyprshadeSubcommand::Serve(serve) => serve.execute(config),
+            HyprshadeSubcommand::Shader(shader) => shader.execute(config),
             HyprshadeSubcommand::Toggle(toggle) => toggle.execute(config),
+            HyprshadeSubcommand::Uninhibit(uninhibit) => uninhibit.execute(config),
+            HyprshadeSubcommand::Which(which) => which.execute(config),
+        };
+
+        // Taken even if the subcommand failed, so that `serve` doesn't mistake it for what a later
+        // request loaded
+        let loaded = hyprctl::shader::take_set();
+        let exit_code = exit_code?;
+        if let Some(loaded) = loaded {
+            collect_garbage(loaded.as_deref());
         }
+
+        Ok(exit_code)
+    }
+}
+
+/// Removes shader instances left unused after changing the shader to `loaded`, which is best
+/// effort.
+pub fn collect_garbage(loaded: Option<&Path>) {
+    if let Err(err) = gc::collect(loaded, false) {
+        debug!("Failed to remove unused shader instances: {err:?}");
     }
 }
 
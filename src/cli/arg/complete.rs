@@ -0,0 +1,286 @@
+//! Completers for dynamic shell completions, which run when the shell asks for candidates.
+
+use std::{
+    env,
+    ffi::{OsStr, OsString},
+    path::{MAIN_SEPARATOR, Path},
+};
+
+use clap::{Command, CommandFactory};
+use clap_complete::engine::{CompletionCandidate, PathCompleter, ValueCompleter};
+
+use crate::{
+    builtin::BuiltinShader,
+    cli::{Cli, arg::var::VarArg, read_config},
+    constants::HYPRSHADE_CONFIG_FILE_ENV,
+    instance,
+    pack::Pack,
+    resolver::{Candidate, Resolver, available_shaders},
+};
+
+/// Completes shader names, or paths once a separator is typed.
+pub fn shader(current: &OsStr) -> Vec<CompletionCandidate> {
+    let Some(current) = current.to_str() else {
+        return Vec::new();
+    };
+    if current.contains(MAIN_SEPARATOR) {
+        return PathCompleter::file().complete(OsStr::new(current));
+    }
+
+    CommandLine::read().load_shader_dirs();
+    available_shaders()
+        .into_iter()
+        .filter(|(name, _)| name.starts_with(current))
+        .map(|(name, candidate)| {
+            let help = match candidate {
+                Candidate::File { path, .. } => path.display().to_string(),
+                Candidate::Builtin(_) => "builtin".to_owned(),
+            };
+            CompletionCandidate::new(name).help(Some(help.into()))
+        })
+        .collect()
+}
+
+pub fn builtin_shader() -> Vec<CompletionCandidate> {
+    BuiltinShader::all()
+        .map(|builtin| CompletionCandidate::new(builtin.name()))
+        .collect()
+}
+
+pub fn instance() -> Vec<CompletionCandidate> {
+    instance::running()
+        .unwrap_or_default()
+        .into_iter()
+        .map(CompletionCandidate::new)
+        .collect()
+}
+
+pub fn installed_pack() -> Vec<CompletionCandidate> {
+    Pack::installed()
+        .unwrap_or_default()
+        .iter()
+        .map(|pack| CompletionCandidate::new(pack.name()))
+        .collect()
+}
+
+/// Completes `--var` for SHADER.
+pub fn var(current: &OsStr) -> Vec<CompletionCandidate> {
+    let command_line = CommandLine::read();
+    command_line.load_shader_dirs();
+    match command_line.positional(1) {
+        Some(shader) => shader_var(shader, current),
+        None => Vec::new(),
+    }
+}
+
+/// Completes `--var-fallback` for the shader given to `--fallback`.
+pub fn var_fallback(current: &OsStr) -> Vec<CompletionCandidate> {
+    let command_line = CommandLine::read();
+    command_line.load_shader_dirs();
+    match command_line.option("fallback") {
+        Some(shader) => shader_var(shader, current),
+        None => Vec::new(),
+    }
+}
+
+/// Completes `KEY=` from the variables of `shader`, then `KEY=VALUE` for enum and boolean
+/// variables.
+fn shader_var(shader: &str, current: &OsStr) -> Vec<CompletionCandidate> {
+    let Some(current) = current.to_str() else {
+        return Vec::new();
+    };
+    let Ok(metadata) = Resolver::with_cli_arg(shader)
+        .resolve()
+        .map_err(eyre::Report::from)
+        .and_then(|shader| Ok(shader.metadata()?))
+    else {
+        return Vec::new();
+    };
+    let Some(toml::Value::Table(variables)) = metadata.get("variables") else {
+        return Vec::new();
+    };
+    let variables = leaf_variables(variables, "");
+
+    match current.split_once(VarArg::ASSIGN) {
+        None => variables
+            .into_iter()
+            .filter(|(key, _)| key.starts_with(current))
+            .map(|(key, variable)| {
+                let help = variable
+                    .get("description")
+                    .and_then(toml::Value::as_str)
+                    .map(|description| {
+                        description
+                            .lines()
+                            .next()
+                            .unwrap_or_default()
+                            .to_owned()
+                            .into()
+                    });
+                CompletionCandidate::new(format!("{key}{}", VarArg::ASSIGN)).help(help)
+            })
+            .collect(),
+        Some((key, value)) => {
+            let Some((_, variable)) = variables.into_iter().find(|(k, _)| k == key) else {
+                return Vec::new();
+            };
+            let values: Vec<&str> = match variable.get("type").and_then(toml::Value::as_str) {
+                Some("enum") => variable
+                    .get("variants")
+                    .and_then(toml::Value::as_array)
+                    .into_iter()
+                    .flatten()
+                    .filter_map(toml::Value::as_str)
+                    .collect(),
+                Some("bool") => vec!["true", "false"],
+                _ => Vec::new(),
+            };
+            values
+                .into_iter()
+                .filter(|v| v.starts_with(value))
+                .map(|v| CompletionCandidate::new(format!("{key}{}{v}", VarArg::ASSIGN)))
+                .collect()
+        }
+    }
+}
+
+/// Flattens nested tables of variables into dotted keys (e.g. `balance.red`).
+fn leaf_variables<'a>(variables: &'a toml::Table, prefix: &str) -> Vec<(String, &'a toml::Table)> {
+    variables
+        .iter()
+        .filter_map(|(key, value)| Some((key, value.as_table()?)))
+        .flat_map(|(key, table)| {
+            let key = format!("{prefix}{key}");
+            if table.contains_key("type") {
+                vec![(key, table)]
+            } else {
+                leaf_variables(table, &format!("{key}{}", VarArg::LHS_SEP))
+            }
+        })
+        .collect()
+}
+
+/// Arguments of the command line being completed, as passed after `--` by the shell integration
+struct CommandLine {
+    command: Command,
+    args: Vec<String>,
+}
+
+impl CommandLine {
+    fn read() -> Self {
+        let args = env::args_os()
+            .skip_while(|arg| arg != "--")
+            .skip(2)
+            .map(|arg| arg.to_string_lossy().into_owned())
+            .collect();
+        let mut command = Cli::command();
+        command.build();
+        Self { command, args }
+    }
+
+    /// Makes shader directories from the config file available, as [`Cli`] does when executed.
+    fn load_shader_dirs(&self) {
+        let path = self
+            .option("config")
+            .map(OsString::from)
+            .or_else(|| env::var_os(HYPRSHADE_CONFIG_FILE_ENV));
+        if let Ok(Some(config)) = read_config(path.as_deref().map(Path::new)) {
+            config.add_shader_dirs();
+        }
+    }
+
+    /// The value of the long `option`, e.g. `--fallback NAME` or `--fallback=NAME`.
+    fn option(&self, option: &str) -> Option<&str> {
+        let flag = format!("--{option}");
+        let mut args = self.args.iter();
+        while let Some(arg) = args.next() {
+            if *arg == flag {
+                return args.next().map(String::as_str);
+            }
+            if let Some(value) = arg.strip_prefix(&flag).and_then(|a| a.strip_prefix('=')) {
+                return Some(value);
+            }
+        }
+        None
+    }
+
+    /// The positional argument at `index`, counting the subcommand as 0.
+    fn positional(&self, index: usize) -> Option<&str> {
+        let mut command = &self.command;
+        let mut positionals = Vec::new();
+        let mut args = self.args.iter();
+        while let Some(arg) = args.next() {
+            if let Some(long) = arg.strip_prefix("--") {
+                if !long.contains('=') && takes_value(command, long) {
+                    args.next();
+                }
+            } else if !arg.starts_with('-') {
+                if positionals.is_empty() {
+                    command = command.find_subcommand(arg).unwrap_or(command);
+                }
+                positionals.push(arg.as_str());
+            }
+        }
+        positionals.get(index).copied()
+    }
+}
+
+fn takes_value(command: &Command, long: &str) -> bool {
+    command
+        .get_arguments()
+        .any(|arg| arg.get_long() == Some(long) && arg.get_action().takes_values())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn values(candidates: Vec<CompletionCandidate>) -> Vec<String> {
+        candidates
+            .iter()
+            .map(|c| c.get_value().to_string_lossy().into_owned())
+            .collect()
+    }
+
+    #[test]
+    fn builtin_var_keys() {
+        let keys = values(shader_var("vibrance", OsStr::new("bal")));
+        assert_eq!(
+            keys,
+            ["balance.blue=", "balance.green=", "balance.red="].map(String::from)
+        );
+    }
+
+    #[test]
+    fn builtin_var_enum_values() {
+        let values = values(shader_var("color-filter", OsStr::new("type=")));
+        assert!(values.contains(&"type=protanopia".to_owned()), "{values:?}");
+        assert!(values.iter().all(|v| v.starts_with("type=")));
+    }
+
+    #[test]
+    fn command_line() {
+        let command_line = CommandLine {
+            command: {
+                let mut command = Cli::command();
+                command.build();
+                command
+            },
+            args: [
+                "--config",
+                "a.toml",
+                "toggle",
+                "--var",
+                "x=1",
+                "vibrance",
+                "--fallback=grayscale",
+            ]
+            .map(String::from)
+            .to_vec(),
+        };
+        assert_eq!(command_line.positional(0), Some("toggle"));
+        assert_eq!(command_line.positional(1), Some("vibrance"));
+        assert_eq!(command_line.option("config"), Some("a.toml"));
+        assert_eq!(command_line.option("fallback"), Some("grayscale"));
+    }
+}
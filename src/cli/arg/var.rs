@@ -180,8 +180,8 @@ fn check_no_conflicts<C: CommandFactory>(vars: &[VarArg]) -> Result<(), clap::Er
 }
 
 impl VarArg {
-    const LHS_SEP: &'static str = ".";
-    const ASSIGN: &'static str = "=";
+    pub const LHS_SEP: &'static str = ".";
+    pub const ASSIGN: &'static str = "=";
 
     fn display(&self) -> String {
         format!(
@@ -1,10 +1,50 @@
-use std::{error::Error, io::IsTerminal};
+use std::{
+    cell::RefCell,
+    error::Error,
+    io::{self, IsTerminal},
+};
 
 use clap::Args;
 use color_eyre::Section;
 use eyre::Context;
 use tracing_error::ErrorLayer;
-use tracing_subscriber::{filter::EnvFilter, layer::SubscriberExt, util::SubscriberInitExt};
+use tracing_subscriber::{
+    Layer,
+    filter::{EnvFilter, LevelFilter},
+    layer::SubscriberExt,
+    util::SubscriberInitExt,
+};
+
+thread_local! {
+    /// Warnings and errors logged by this thread while [`capture`] runs
+    static CAPTURED: RefCell<Option<Vec<u8>>> = const { RefCell::new(None) };
+}
+
+/// Runs `f`, and returns the warnings and errors it logged as they would be printed, e.g. so that
+/// `hyprshade serve` can pass them on to the process it runs a subcommand for.
+pub fn capture<T>(f: impl FnOnce() -> T) -> (T, String) {
+    CAPTURED.set(Some(Vec::new()));
+    let ret = f();
+    let captured = CAPTURED.take().unwrap_or_default();
+    (ret, String::from_utf8_lossy(&captured).into_owned())
+}
+
+struct CaptureWriter;
+
+impl io::Write for CaptureWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        CAPTURED.with_borrow_mut(|captured| {
+            if let Some(captured) = captured {
+                captured.extend_from_slice(buf);
+            }
+        });
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
 
 #[derive(Debug, Args)]
 pub struct Instrumentation {
@@ -19,7 +59,8 @@ impl Instrumentation {
 
         let registry = tracing_subscriber::registry()
             .with(filter_layer)
-            .with(ErrorLayer::default());
+            .with(ErrorLayer::default())
+            .with(self.fmt_layer_capture());
 
         match self.formatter() {
             Formatter::Compact => {
@@ -51,6 +92,21 @@ impl Instrumentation {
             .with_line_number(false)
     }
 
+    fn fmt_layer_capture<S>(&self) -> impl tracing_subscriber::Layer<S>
+    where
+        S: tracing::Subscriber + for<'a> tracing_subscriber::registry::LookupSpan<'a>,
+    {
+        tracing_subscriber::fmt::Layer::new()
+            .with_ansi(false)
+            .with_writer(|| CaptureWriter)
+            .compact()
+            .without_time()
+            .with_target(false)
+            .with_file(false)
+            .with_line_number(false)
+            .with_filter(LevelFilter::WARN)
+    }
+
     fn fmt_layer_full<S>(&self) -> impl tracing_subscriber::Layer<S>
     where
         S: tracing::Subscriber + for<'a> tracing_subscriber::registry::LookupSpan<'a>,
@@ -1,25 +1,121 @@
+mod adjust;
+use adjust::Adjust;
 mod auto;
 use auto::Auto;
+mod completions;
+use completions::Completions;
 mod current;
 use current::Current;
+#[cfg(feature = "dbus")]
+mod dbus_service;
+#[cfg(feature = "dbus")]
+use dbus_service::DbusService;
+mod gc;
+use gc::Gc;
+mod inhibit;
+use inhibit::Inhibit;
 mod install;
 use install::Install;
+mod instances;
+use instances::Instances;
 mod ls;
 use ls::Ls;
 mod off;
 use off::Off;
 mod on;
 use on::On;
+mod restore;
+use restore::Restore;
+mod revert;
+use revert::Revert;
+mod serve;
+use serve::Serve;
+mod shader;
+use shader::Shader;
 mod toggle;
 use toggle::Toggle;
+mod uninhibit;
+use uninhibit::Uninhibit;
+mod which;
+use which::Which;
 
 #[derive(Debug, clap::Subcommand)]
 pub enum HyprshadeSubcommand {
+    Adjust(Adjust),
     Auto(Auto),
+    Completions(Completions),
     Current(Current),
+    #[cfg(feature = "dbus")]
+    DbusService(DbusService),
+    Gc(Gc),
+    Inhibit(Inhibit),
     Install(Install),
+    Instances(Instances),
     Ls(Ls),
     Off(Off),
     On(On),
+    Restore(Restore),
+    Revert(Revert),
+    Serve(Serve),
+    Shader(Shader),
     Toggle(Toggle),
+    Uninhibit(Uninhibit),
+    Which(Which),
+}
+
+impl HyprshadeSubcommand {
+    /// Whether the subcommand reads the current shader and decides what to change, which must not
+    /// interleave with another hyprshade process doing the same.
+    pub fn changes_shader(&self) -> bool {
+        match self {
+            Self::Adjust(_)
+            | Self::Auto(_)
+            | Self::Inhibit(_)
+            | Self::Off(_)
+            | Self::On(_)
+            | Self::Revert(_)
+            | Self::Toggle(_)
+            | Self::Uninhibit(_) => true,
+            #[cfg(feature = "dbus")]
+            Self::DbusService(_) => false,
+            Self::Completions(_)
+            | Self::Current(_)
+            | Self::Gc(_)
+            | Self::Install(_)
+            | Self::Instances(_)
+            | Self::Ls(_)
+            | Self::Restore(_)
+            | Self::Serve(_)
+            | Self::Shader(_)
+            | Self::Which(_) => false,
+        }
+    }
+
+    /// Whether the subcommand is run by `hyprshade serve` while it is running. These are the
+    /// subcommands bound to keys and polled by status bars, which run often enough for starting
+    /// up to matter.
+    pub fn is_forwarded(&self) -> bool {
+        match self {
+            Self::Adjust(_)
+            | Self::Auto(_)
+            | Self::Current(_)
+            | Self::Off(_)
+            | Self::On(_)
+            | Self::Toggle(_) => true,
+            #[cfg(feature = "dbus")]
+            Self::DbusService(_) => false,
+            Self::Completions(_)
+            | Self::Gc(_)
+            | Self::Inhibit(_)
+            | Self::Install(_)
+            | Self::Instances(_)
+            | Self::Ls(_)
+            | Self::Restore(_)
+            | Self::Revert(_)
+            | Self::Serve(_)
+            | Self::Shader(_)
+            | Self::Uninhibit(_)
+            | Self::Which(_) => false,
+        }
+    }
 }
@@ -0,0 +1,154 @@
+use std::process::ExitCode;
+
+use clap::{ArgGroup, Parser};
+use color_eyre::Section;
+use eyre::{OptionExt, eyre};
+
+use crate::{
+    cli::CommandExecute, compositor::Hyprland, config::Config, metadata, shader::Shader,
+    template::TemplateData,
+};
+
+const EXAMPLE_SECTION: &str = color_print::cstr!(
+    r#"<bold><underline>Examples:</underline></bold>
+  # lower the color temperature of blue-light-filter by 200K
+  hyprshade adjust temperature -200
+
+  # increase vibrance strength
+  hyprshade adjust strength +0.1
+
+  # switch to the next grayscale conversion type
+  hyprshade adjust type --next
+"#
+);
+
+/**
+Adjust a variable of the current shader relative to its current value
+
+Numeric variables are clamped to the range allowed by the shader. Enum variables can be cycled
+through with --next and --prev, which also toggle boolean variables.
+*/
+#[derive(Debug, Parser)]
+#[command(after_long_help = EXAMPLE_SECTION)]
+#[command(group(ArgGroup::new("change").required(true).args(["delta", "next", "prev"])))]
+pub struct Adjust {
+    /// Variable to adjust (e.g. `strength` or `balance.red`)
+    #[arg(value_name = "KEY")]
+    key: String,
+
+    /// Amount to add to the current value (may be negative)
+    #[arg(allow_negative_numbers = true)]
+    delta: Option<f64>,
+
+    /// Switch to the next variant of an enum variable, or toggle a boolean variable
+    #[arg(long)]
+    next: bool,
+
+    /// Switch to the previous variant of an enum variable, or toggle a boolean variable
+    #[arg(long)]
+    prev: bool,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Change {
+    Delta(f64),
+    Next,
+    Prev,
+}
+
+impl CommandExecute for Adjust {
+    #[tracing::instrument(level = "debug", skip_all)]
+    fn execute(self, _config: Option<&Config>) -> eyre::Result<ExitCode> {
+        let Adjust {
+            key,
+            delta,
+            next,
+            prev,
+        } = self;
+
+        let change = match (delta, next, prev) {
+            (Some(delta), false, false) => Change::Delta(delta),
+            (None, true, false) => Change::Next,
+            (None, false, true) => Change::Prev,
+            _ => unreachable!("DELTA, --next, and --prev are mutually exclusive"),
+        };
+
+        let path: Vec<&str> = key.split('.').collect();
+        if path.iter().any(|s| s.is_empty()) {
+            return Err(eyre!("invalid variable name {key:?}"))
+                .suggestion("Each word in KEY must be separated by exactly one '.'");
+        }
+
+        let instance = Shader::current(&Hyprland)?.ok_or_eyre("no shader is currently active")?;
+        let shader = instance.to_shader()?;
+
+        let metadata = shader.metadata()?;
+        let variable = metadata::variable(&metadata, &path);
+        let current = instance
+            .data()
+            .get_path(&path)
+            .cloned()
+            .or_else(|| variable.and_then(metadata::default_value))
+            .ok_or_else(|| eyre!("shader {:?} has no variable {key:?}", shader.name()))?;
+        let variants: Option<Vec<&str>> = variable
+            .filter(|v| v.get("type").and_then(toml::Value::as_str) == Some("enum"))
+            .and_then(|v| v.get("variants")?.as_array())
+            .map(|variants| variants.iter().filter_map(toml::Value::as_str).collect())
+            .filter(|variants: &Vec<&str>| !variants.is_empty());
+
+        let value = match (change, current, variants) {
+            (Change::Delta(delta), TemplateData::Float(value), _) => {
+                let min = variable.and_then(|v| metadata::number(v.get("min")?));
+                let max = variable.and_then(|v| metadata::number(v.get("max")?));
+                TemplateData::Float(
+                    (value + delta)
+                        .max(min.unwrap_or(f64::NEG_INFINITY))
+                        .min(max.unwrap_or(f64::INFINITY)),
+                )
+            }
+            (Change::Delta(_), _, _) => {
+                return Err(eyre!(
+                    "variable {key:?} of shader {:?} is not numeric",
+                    shader.name()
+                ))
+                .suggestion("Use --next or --prev to change enum and boolean variables");
+            }
+            (change @ (Change::Next | Change::Prev), TemplateData::Enum(value), Some(variants)) => {
+                let normalized = TemplateData::normalize_enum(&value);
+                let len = variants.len();
+                let index = variants
+                    .iter()
+                    .position(|v| TemplateData::normalize_enum(v) == normalized)
+                    .ok_or_else(|| {
+                        eyre!(
+                            "variable {key:?} of shader {:?} has invalid value {value:?}",
+                            shader.name()
+                        )
+                    })
+                    .with_note(|| format!("Valid values are {}", variants.join(", ")))?;
+                let index = match change {
+                    Change::Next => (index + 1) % len,
+                    _ => (index + len - 1) % len,
+                };
+                TemplateData::Enum(variants[index].to_owned())
+            }
+            (Change::Next | Change::Prev, TemplateData::Bool(value), _) => {
+                TemplateData::Bool(!value)
+            }
+            (Change::Next | Change::Prev, _, _) => {
+                return Err(eyre!(
+                    "variable {key:?} of shader {:?} has no known variants",
+                    shader.name()
+                ))
+                .note("--next and --prev only apply to enum variables listed in the shader's metadata");
+            }
+        };
+
+        let mut data = instance.data().clone();
+        data.insert_path(&path, value);
+
+        shader.on_remembering(&Hyprland, &data, true)?;
+
+        Ok(ExitCode::SUCCESS)
+    }
+}
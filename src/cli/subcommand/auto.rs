@@ -4,15 +4,19 @@ use clap::Parser;
 use color_eyre::Section;
 use const_format::formatcp;
 use eyre::{Context, OptionExt};
+use tracing::debug;
 
 use crate::{
     cli::CommandExecute,
+    compositor::Hyprland,
     config::Config,
     constants::{README_CONFIGURATION, README_SCHEDULING},
+    inhibit::Inhibit,
     schedule::Schedule,
     shader::Shader,
     time::now,
-    util::ConfigSection,
+    timed::TimedShader,
+    util::{ConfigSection, ShaderNameSuggestion},
 };
 
 const ABOUT: &str = "Activate the currently scheduled shader";
@@ -25,29 +29,57 @@ const LONG_ABOUT: &str = formatcp!(
     "
 );
 
-#[derive(Debug, Parser)]
+#[derive(Debug, Default, Parser)]
 #[command(about = ABOUT, long_about = LONG_ABOUT)]
-pub struct Auto;
+pub struct Auto {
+    /// Turn on the scheduled shader even if it is already on
+    #[arg(long)]
+    force: bool,
+}
 
 impl CommandExecute for Auto {
     #[tracing::instrument(level = "debug", skip_all)]
     fn execute(self, config: Option<&Config>) -> eyre::Result<ExitCode> {
+        let Auto { force } = self;
         let now = now();
         let config = config
             .ok_or_eyre("no configuration file found")
             .warning("A configuration file is required to call this command")
             .with_suggestion(|| format!("For more information, see {README_CONFIGURATION}"))?;
 
-        if let Some(shader) = Schedule::with_config(config).scheduled_shader(&now.time())
+        if let Some(inhibit) = Inhibit::active(|| now)? {
+            debug!(
+                "Inhibited until {:?}, leaving the screen alone",
+                inhibit.until()
+            );
+            return Ok(ExitCode::SUCCESS);
+        }
+        if let Some(timed) = TimedShader::read()? {
+            if !timed.is_expired(&now) {
+                debug!(
+                    "Timed shader is on until {:?}, leaving the screen alone",
+                    timed.until()
+                );
+                return Ok(ExitCode::SUCCESS);
+            }
+            TimedShader::cancel()?;
+        }
+
+        if let Some(shader) = Schedule::with_config(config).scheduled_shader(&now)
+            .shader_name_suggestion()
             .wrap_err("resolving shader in config")
             .config_section(config.path())
             .suggestion("Change the shader name in your configuration, or make sure a shader by that name exists")
             .with_suggestion(|| format!("For more information, see {README_CONFIGURATION}"))?
         {
-            let data = config.data(shader.name());
-            shader.on(data.unwrap_or(&Default::default()))?;
+            let data = config.data(shader.name()).cloned().unwrap_or_default();
+            if force {
+                shader.on_remembering(&Hyprland, &data, true)?;
+            } else {
+                shader.on_if_changed_remembering(&Hyprland, &data, true)?;
+            }
         } else {
-            Shader::off()?;
+            Shader::off_remembering(&Hyprland, true)?;
         }
 
         Ok(ExitCode::SUCCESS)
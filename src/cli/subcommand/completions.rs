@@ -0,0 +1,46 @@
+use std::{io, process::ExitCode};
+
+use clap::{Parser, builder::PossibleValuesParser};
+use clap_complete::env::Shells;
+
+use crate::{cli::CommandExecute, config::Config, constants::COMPLETE_ENV};
+
+const EXAMPLE_SECTION: &str = color_print::cstr!(
+    r#"<bold><underline>Examples:</underline></bold>
+  # enable completions in the current bash session
+  source <<(hyprshade completions bash)
+
+  # enable completions in fish
+  hyprshade completions fish > ~/.config/fish/completions/hyprshade.fish
+"#
+);
+
+/**
+Print a script that enables shell completions
+
+The script calls back into hyprshade whenever completions are requested, so shader names and
+variables complete from what is currently installed.
+*/
+#[derive(Debug, Parser)]
+#[command(after_long_help = EXAMPLE_SECTION)]
+pub struct Completions {
+    /// Shell to print the script for
+    #[arg(value_parser = PossibleValuesParser::new(Shells::builtins().names()))]
+    shell: String,
+}
+
+impl CommandExecute for Completions {
+    #[tracing::instrument(level = "debug", skip_all)]
+    fn execute(self, _config: Option<&Config>) -> eyre::Result<ExitCode> {
+        let Completions { shell } = self;
+
+        let shells = Shells::builtins();
+        let completer = shells
+            .completer(&shell)
+            .expect("shell should be validated by clap");
+        let bin = env!("CARGO_PKG_NAME");
+        completer.write_registration(COMPLETE_ENV, bin, bin, bin, &mut io::stdout())?;
+
+        Ok(ExitCode::SUCCESS)
+    }
+}
@@ -1,12 +1,28 @@
-use std::process::ExitCode;
+use std::{
+    io::{self, Write},
+    process::ExitCode,
+};
 
 use clap::Parser;
 use tracing::warn;
 
-use crate::{cli::CommandExecute, config::Config, shader::Shader};
+use crate::{
+    cli::CommandExecute,
+    compositor::Hyprland,
+    config::Config,
+    inhibit::Inhibit,
+    shader::Shader,
+    time::now,
+    timed::{Revert, TimedShader},
+};
 
 /**
 Show the current shader
+
+A warning is logged while a shader turned on with `on --for` is about to revert, or while the
+schedule is inhibited. With --long, also shows where the shader comes from, the file Hyprland has loaded and whether it
+still matches its source, when a shader turned on with `on --for` reverts, and whether the schedule
+is inhibited.
 */
 #[derive(Debug, Parser)]
 pub struct Current {
@@ -18,16 +34,81 @@ pub struct Current {
 impl CommandExecute for Current {
     #[tracing::instrument(level = "debug", skip_all)]
     fn execute(self, _config: Option<&Config>) -> eyre::Result<ExitCode> {
-        let Self { long } = self;
+        self.write(&mut io::stdout().lock())?;
+        Ok(ExitCode::SUCCESS)
+    }
+}
+
+impl Current {
+    /// Writes what `hyprshade current` prints to `out`.
+    pub fn write(&self, out: &mut impl Write) -> eyre::Result<()> {
+        let Self { long } = *self;
+
+        let instance = Shader::current(&Hyprland)?;
+        let shader = instance.clone().map(Shader::try_from).transpose()?;
 
-        if let Some(shader) = Shader::current()?.map(Shader::try_from).transpose()? {
-            if long {
-                todo!("current --long")
-            } else {
-                println!("{}", shader.name());
+        if !long {
+            if let Some(shader) = shader {
+                writeln!(out, "{}", shader.name())?;
             }
+            return warn_state();
         }
 
-        Ok(ExitCode::SUCCESS)
+        if let (Some(instance), Some(shader)) = (instance, shader) {
+            writeln!(out, "name: {}", shader.name())?;
+            match shader.path() {
+                Some(path) => writeln!(out, "source: {}", path.display())?,
+                None => writeln!(out, "source: builtin")?,
+            }
+            writeln!(out, "loaded: {}", instance.instance_path().display())?;
+            if shader.path() != Some(instance.instance_path()) {
+                match instance.is_up_to_date()? {
+                    true => writeln!(out, "rendered: up to date")?,
+                    false => writeln!(
+                        out,
+                        "rendered: outdated (turn it on with --force to update)"
+                    )?,
+                }
+            }
+        }
+        if let Some(timed) = TimedShader::read()? {
+            writeln!(out, "reverts: {}", reverts(&timed))?;
+        }
+        if let Some(inhibit) = Inhibit::active(now)? {
+            writeln!(out, "inhibited: {}", inhibited(&inhibit))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Warns about a pending revert or an inhibited schedule, which output listing only names would
+/// leave out.
+pub(super) fn warn_state() -> eyre::Result<()> {
+    if let Some(timed) = TimedShader::read()? {
+        warn!("The shader reverts {}", reverts(&timed));
+    }
+    if let Some(inhibit) = Inhibit::active(now)? {
+        warn!("The schedule is inhibited {}", inhibited(&inhibit));
+    }
+    Ok(())
+}
+
+fn reverts(timed: &TimedShader) -> String {
+    let to = match timed.revert() {
+        Revert::Previous(Some(previous)) => match previous.to_shader() {
+            Ok(shader) => shader.name().to_owned(),
+            Err(_) => previous.instance_path().display().to_string(),
+        },
+        Revert::Previous(None) => "off".to_owned(),
+        Revert::Schedule => "schedule".to_owned(),
+    };
+    format!("at {} to {to}", timed.until().format("%Y-%m-%d %H:%M:%S"))
+}
+
+fn inhibited(inhibit: &Inhibit) -> String {
+    match inhibit.until() {
+        Some(until) => format!("until {}", until.format("%Y-%m-%d %H:%M:%S")),
+        None => "until uninhibit".to_owned(),
     }
 }
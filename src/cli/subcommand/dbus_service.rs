@@ -0,0 +1,197 @@
+use std::{
+    collections::HashMap,
+    process::ExitCode,
+    sync::{Mutex, PoisonError},
+};
+
+use clap::Parser;
+use tracing::{debug, info};
+use zbus::{fdo, interface, object_server::SignalEmitter};
+
+use super::{Off, On, Toggle};
+use crate::{
+    cli::{CommandExecute, LiveConfig, collect_garbage},
+    compositor::Hyprland,
+    config::Config,
+    hyprctl,
+    lock::RuntimeLock,
+    schedule::Schedule,
+    shader::{Shader, ShaderInstance},
+    time::now,
+};
+
+const EXAMPLE_SECTION: &str = color_print::cstr!(
+    r#"<bold><underline>Examples:</underline></bold>
+  # in hyprland.conf
+  exec-once = hyprshade dbus-service
+
+  # turn on vibrance with a variable
+  busctl --user call io.github.loqusion.Hyprshade /io/github/loqusion/Hyprshade \
+    io.github.loqusion.Hyprshade1 On 'sa{ss}' vibrance 1 balance.red 0.5
+"#
+);
+
+pub const BUS_NAME: &str = "io.github.loqusion.Hyprshade";
+pub const OBJECT_PATH: &str = "/io/github/loqusion/Hyprshade";
+
+/**
+Serve shader changes over D-Bus
+
+Owns io.github.loqusion.Hyprshade on the session bus, with the io.github.loqusion.Hyprshade1
+interface at /io/github/loqusion/Hyprshade. Its On, Off, and Toggle methods work like the
+subcommands of the same name, taking variables as a dictionary from KEY to VALUE. Current and
+Schedule return the name of the current and the scheduled shader, or an empty string if there is
+none. The ShaderChanged signal is emitted whenever the shader is changed through the service. The
+configuration file is read again when it changes.
+*/
+#[derive(Debug, Parser)]
+#[command(after_long_help = EXAMPLE_SECTION)]
+pub struct DbusService {}
+
+impl CommandExecute for DbusService {
+    #[tracing::instrument(level = "debug", skip_all)]
+    fn execute(self, config: Option<&Config>) -> eyre::Result<ExitCode> {
+        let service = Service {
+            config: Mutex::new(LiveConfig::new(config.cloned())),
+        };
+        let connection = zbus::block_on(
+            zbus::connection::Builder::session()?
+                .name(BUS_NAME)?
+                .serve_at(OBJECT_PATH, service)?
+                .internal_executor(false)
+                .build(),
+        )?;
+        info!("Serving {BUS_NAME} on the session bus");
+
+        // Handle requests on this thread rather than zbus's own, so that the service exits instead
+        // of hanging if one of them panics
+        zbus::block_on(async {
+            loop {
+                connection.executor().tick().await;
+            }
+        })
+    }
+}
+
+struct Service {
+    config: Mutex<LiveConfig>,
+}
+
+impl Service {
+    /// Runs a subcommand as if it was given `args`, like hyprshade processes changing the shader
+    /// do, and returns the name of the shader if it changed, including its variables.
+    fn change<C>(&self, args: &[String]) -> eyre::Result<Option<String>>
+    where
+        C: Parser + CommandExecute,
+    {
+        let command = C::try_parse_from(args)?;
+        let mut config = self.config.lock().unwrap_or_else(PoisonError::into_inner);
+        let config = config.reload()?;
+        if let Some(config) = config {
+            config.add_shader_dirs();
+        }
+
+        let _lock = RuntimeLock::acquire()?;
+        let before = Shader::current(&Hyprland)?;
+        let result = command.execute(config);
+        let loaded = hyprctl::shader::take_set();
+        result?;
+        if let Some(loaded) = loaded {
+            collect_garbage(loaded.as_deref());
+        }
+        let after = Shader::current(&Hyprland)?;
+
+        if before == after {
+            return Ok(None);
+        }
+        debug!("Shader changed from {before:?} to {after:?}");
+        Ok(Some(instance_name(after)?))
+    }
+
+    async fn notify(emitter: &SignalEmitter<'_>, changed: Option<String>) -> fdo::Result<()> {
+        if let Some(shader) = changed {
+            Self::shader_changed(emitter, &shader).await?;
+        }
+        Ok(())
+    }
+}
+
+#[interface(name = "io.github.loqusion.Hyprshade1")]
+impl Service {
+    async fn on(
+        &self,
+        #[zbus(signal_emitter)] emitter: SignalEmitter<'_>,
+        shader: String,
+        vars: HashMap<String, String>,
+    ) -> fdo::Result<()> {
+        let args = command_args("on", Some(shader), vars);
+        let changed = self.change::<On>(&args).map_err(failed)?;
+        Self::notify(&emitter, changed).await
+    }
+
+    async fn off(&self, #[zbus(signal_emitter)] emitter: SignalEmitter<'_>) -> fdo::Result<()> {
+        let args = command_args("off", None, HashMap::new());
+        let changed = self.change::<Off>(&args).map_err(failed)?;
+        Self::notify(&emitter, changed).await
+    }
+
+    async fn toggle(
+        &self,
+        #[zbus(signal_emitter)] emitter: SignalEmitter<'_>,
+        shader: String,
+        vars: HashMap<String, String>,
+    ) -> fdo::Result<()> {
+        let shader = (!shader.is_empty()).then_some(shader);
+        let args = command_args("toggle", shader, vars);
+        let changed = self.change::<Toggle>(&args).map_err(failed)?;
+        Self::notify(&emitter, changed).await
+    }
+
+    async fn current(&self) -> fdo::Result<String> {
+        current_name().map_err(failed)
+    }
+
+    async fn schedule(&self) -> fdo::Result<String> {
+        let mut config = self.config.lock().unwrap_or_else(PoisonError::into_inner);
+        let Some(config) = config.reload().map_err(|err| failed(err.into()))? else {
+            return Ok(String::new());
+        };
+        let shader = Schedule::with_config(config)
+            .scheduled_shader(&now())
+            .map_err(|err| failed(err.into()))?;
+        Ok(shader.map(|s| s.name().to_owned()).unwrap_or_default())
+    }
+
+    #[zbus(signal)]
+    async fn shader_changed(emitter: &SignalEmitter<'_>, shader: &str) -> zbus::Result<()>;
+}
+
+fn command_args(name: &str, shader: Option<String>, vars: HashMap<String, String>) -> Vec<String> {
+    let mut vars = vars.into_iter().collect::<Vec<_>>();
+    vars.sort();
+
+    let mut args = vec![name.to_owned()];
+    // Attached, so that values starting with `-` aren't taken for options
+    args.extend(
+        vars.into_iter()
+            .map(|(key, value)| format!("--var={key}={value}")),
+    );
+    if let Some(shader) = shader {
+        // Keeps a shader name such as `--help` from being taken for an option too
+        args.extend(["--".to_owned(), shader]);
+    }
+    args
+}
+
+fn current_name() -> eyre::Result<String> {
+    instance_name(Shader::current(&Hyprland)?)
+}
+
+fn instance_name(instance: Option<ShaderInstance>) -> eyre::Result<String> {
+    let shader = instance.map(Shader::try_from).transpose()?;
+    Ok(shader.map(|s| s.name().to_owned()).unwrap_or_default())
+}
+
+fn failed(err: eyre::Report) -> fdo::Error {
+    fdo::Error::Failed(format!("{err:#}"))
+}
@@ -0,0 +1,39 @@
+use std::process::ExitCode;
+
+use clap::Parser;
+
+use eyre::Context;
+
+use crate::{cli::CommandExecute, config::Config, gc, hyprctl, lock::RuntimeLock};
+
+/**
+Remove unused shader instances from the runtime directory
+
+Shaders are rendered into the runtime directory when turned on. Rendered shaders which aren't
+loaded and weren't turned on in the last day are removed, which also happens whenever the shader
+changes.
+*/
+#[derive(Debug, Parser)]
+pub struct Gc {
+    /// Print the files that would be removed instead of removing them
+    #[arg(long)]
+    dry_run: bool,
+}
+
+impl CommandExecute for Gc {
+    #[tracing::instrument(level = "debug", skip_all)]
+    fn execute(self, _config: Option<&Config>) -> eyre::Result<ExitCode> {
+        let Gc { dry_run } = self;
+
+        let _lock = RuntimeLock::acquire()?;
+        let loaded = hyprctl::shader::get().wrap_err("failed to find current screen shader")?;
+        let garbage = gc::collect(loaded.as_deref(), dry_run)?;
+        if dry_run {
+            for path in garbage {
+                println!("{}", path.display());
+            }
+        }
+
+        Ok(ExitCode::SUCCESS)
+    }
+}
@@ -0,0 +1,96 @@
+use std::{process::ExitCode, time::Duration};
+
+use chrono::{DateTime, Local, NaiveTime, TimeDelta, TimeZone};
+use clap::{ArgGroup, Parser};
+use eyre::{OptionExt, eyre};
+
+use crate::{
+    cli::CommandExecute, compositor::Hyprland, config::Config, inhibit::Inhibit as InhibitState,
+    shader::Shader, time::now,
+};
+
+const EXAMPLE_SECTION: &str = color_print::cstr!(
+    r#"<bold><underline>Examples:</underline></bold>
+  # keep the screen unshaded for a 45 minute screen-share
+  hyprshade inhibit --for 45m
+
+  # pause the schedule until 5 PM, keeping the current shader
+  hyprshade inhibit --until 17:00 --keep-shader
+"#
+);
+
+/**
+Turn off the screen shader and pause the schedule
+
+While inhibited, `hyprshade auto` leaves the screen alone. The schedule resumes when the inhibit
+expires, or when `hyprshade uninhibit` is called. Inhibiting again replaces the previous inhibit.
+*/
+#[derive(Debug, Parser)]
+#[command(after_long_help = EXAMPLE_SECTION)]
+#[command(group(ArgGroup::new("end").args(["duration", "until"])))]
+pub struct Inhibit {
+    /// How long to inhibit for (e.g. `45m` or `1h30m`)
+    #[arg(long = "for", value_name = "DURATION", value_parser = humantime::parse_duration)]
+    duration: Option<Duration>,
+
+    /// Time of day to inhibit until (e.g. `17:00`), today or else tomorrow, in the time zone of the
+    /// schedule
+    #[arg(long, value_name = "TIME", value_parser = parse_time)]
+    until: Option<NaiveTime>,
+
+    /// Keep the current shader on instead of turning it off
+    #[arg(long)]
+    keep_shader: bool,
+}
+
+impl CommandExecute for Inhibit {
+    #[tracing::instrument(level = "debug", skip_all)]
+    fn execute(self, config: Option<&Config>) -> eyre::Result<ExitCode> {
+        let Inhibit {
+            duration,
+            until,
+            keep_shader,
+        } = self;
+
+        let now = now();
+        let until = match (duration, until) {
+            (Some(duration), _) => Some(now + TimeDelta::from_std(duration)?),
+            (_, Some(time)) => Some(match config.and_then(Config::timezone) {
+                Some(timezone) => {
+                    next_occurrence(&now.with_timezone(&timezone), time)?.with_timezone(&Local)
+                }
+                None => next_occurrence(&now, time)?,
+            }),
+            (None, None) => None,
+        };
+
+        InhibitState::new(until).start()?;
+        if !keep_shader {
+            Shader::off_remembering(&Hyprland, true)?;
+        }
+
+        Ok(ExitCode::SUCCESS)
+    }
+}
+
+fn parse_time(s: &str) -> Result<NaiveTime, chrono::ParseError> {
+    NaiveTime::parse_from_str(s, "%H:%M").or_else(|_| NaiveTime::parse_from_str(s, "%H:%M:%S"))
+}
+
+/// Returns the next time after `now` that the clock shows `time`.
+fn next_occurrence<Tz: TimeZone>(
+    now: &DateTime<Tz>,
+    time: NaiveTime,
+) -> eyre::Result<DateTime<Tz>> {
+    let today = now
+        .with_time(time)
+        .earliest()
+        .ok_or_else(|| eyre!("{time} does not exist today"))?;
+    if today > *now {
+        return Ok(today);
+    }
+    (now.clone() + TimeDelta::days(1))
+        .with_time(time)
+        .earliest()
+        .ok_or_eyre(format!("{time} does not exist tomorrow"))
+}
@@ -0,0 +1,26 @@
+use std::process::ExitCode;
+
+use clap::Parser;
+use eyre::Context;
+
+use crate::{cli::CommandExecute, config::Config, instance};
+
+/**
+List running Hyprland instances
+
+Prints the signature of each instance found in $XDG_RUNTIME_DIR/hypr, which can be passed to
+--instance to act on that instance rather than the one hyprshade is running in.
+*/
+#[derive(Debug, Parser)]
+pub struct Instances {}
+
+impl CommandExecute for Instances {
+    #[tracing::instrument(level = "debug", skip_all)]
+    fn execute(self, _config: Option<&Config>) -> eyre::Result<ExitCode> {
+        for signature in instance::running().wrap_err("failed to list Hyprland instances")? {
+            println!("{signature}");
+        }
+
+        Ok(ExitCode::SUCCESS)
+    }
+}
@@ -1,16 +1,23 @@
 use std::process::ExitCode;
 
 use clap::Parser;
-use color_eyre::eyre::eyre;
 
-use crate::{cli::CommandExecute, config::Config};
+use super::current::warn_state;
+use crate::{
+    cli::CommandExecute,
+    config::Config,
+    resolver::{Candidate, available_shaders},
+};
 
 /**
-TODO: write help text
+List shaders that can be turned on by name
+
+A name found in more than one place is listed once, as the shader it resolves to. Like `current`,
+a warning is logged while a shader is about to revert or the schedule is inhibited.
 */
 #[derive(Debug, Parser)]
 pub struct Ls {
-    /// TODO: write help text
+    /// Also show where each shader resolves to
     #[arg(short, long)]
     long: bool,
 }
@@ -18,8 +25,26 @@ pub struct Ls {
 impl CommandExecute for Ls {
     #[tracing::instrument(level = "debug", skip_all)]
     fn execute(self, _config: Option<&Config>) -> eyre::Result<ExitCode> {
-        let Ls { long: _ } = self;
+        let Ls { long } = self;
+
+        let shaders = available_shaders();
+        if !long {
+            for name in shaders.keys() {
+                println!("{name}");
+            }
+            warn_state()?;
+            return Ok(ExitCode::SUCCESS);
+        }
+
+        let width = shaders.keys().map(|name| name.len()).max().unwrap_or(0);
+        for (name, candidate) in shaders {
+            match candidate {
+                Candidate::File { path, .. } => println!("{name:width$}  {}", path.display()),
+                Candidate::Builtin(_) => println!("{name:width$}  (builtin)"),
+            }
+        }
+        warn_state()?;
 
-        Err(eyre!("Not implemented"))
+        Ok(ExitCode::SUCCESS)
     }
 }
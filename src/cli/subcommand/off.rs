@@ -2,10 +2,14 @@ use std::process::ExitCode;
 
 use clap::Parser;
 
-use crate::{cli::CommandExecute, config::Config, shader::Shader};
+use crate::{
+    cli::CommandExecute, compositor::Hyprland, config::Config, shader::Shader, timed::TimedShader,
+};
 
 /**
 Turn off shader
+
+Turning off the shader cancels reverting a shader turned on with `on --for`.
 */
 #[derive(Debug, Parser)]
 pub struct Off;
@@ -13,7 +17,8 @@ pub struct Off;
 impl CommandExecute for Off {
     #[tracing::instrument(level = "debug", skip_all)]
     fn execute(self, _config: Option<&Config>) -> eyre::Result<ExitCode> {
-        Shader::off()?;
+        TimedShader::cancel()?;
+        Shader::off_remembering(&Hyprland, true)?;
 
         Ok(ExitCode::SUCCESS)
     }
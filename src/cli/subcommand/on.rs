@@ -1,32 +1,75 @@
-use std::process::ExitCode;
+use std::{process::ExitCode, time::Duration};
 
-use clap::Parser;
+use chrono::TimeDelta;
+use clap::{Parser, ValueEnum};
+use clap_complete::ArgValueCompleter;
+use eyre::OptionExt;
 use tracing::warn;
 
 use crate::{
     cli::{
         CommandExecute,
         arg::{
+            complete,
             help::{SHADER_HELP, SHADER_HELP_LONG},
             var::{MergeVarArg, VarArg, VarArgParser},
         },
     },
+    compositor::Hyprland,
     config::Config,
     resolver::Resolver,
+    shader::Shader,
     template::MergeDeep,
+    time::now,
+    timed::{Revert, TimedShader},
+    util::ShaderNameSuggestion,
 };
 
+const EXAMPLE_SECTION: &str = color_print::cstr!(
+    r#"<bold><underline>Examples:</underline></bold>
+  # turn on grayscale for a 20 minute focus block, then turn the previous shader back on
+  hyprshade on grayscale --for 20m
+
+  # turn on blue-light-filter for an hour, then turn on whatever is scheduled
+  hyprshade on blue-light-filter --for 1h --then schedule
+"#
+);
+
 /**
 Turn on a shader
+
+Turning on a shader cancels reverting a shader turned on with --for.
 */
 #[derive(Debug, Parser)]
+#[command(after_long_help = EXAMPLE_SECTION)]
 pub struct On {
-    #[arg(help = SHADER_HELP, long_help = SHADER_HELP_LONG)]
+    #[arg(help = SHADER_HELP, long_help = SHADER_HELP_LONG, add = ArgValueCompleter::new(complete::shader))]
     shader: String,
 
     /// Configuration variable used in rendering SHADER (may be specified multiple times)
-    #[arg(long, value_name = "KEY=VALUE", value_parser = VarArgParser)]
+    #[arg(long, value_name = "KEY=VALUE", value_parser = VarArgParser, add = ArgValueCompleter::new(complete::var))]
     var: Vec<VarArg>,
+
+    /// Revert SHADER after DURATION (e.g. `20m` or `1h30m`)
+    #[arg(long = "for", value_name = "DURATION", value_parser = humantime::parse_duration)]
+    duration: Option<Duration>,
+
+    /// What to revert to once DURATION is up
+    #[arg(long, value_enum, default_value_t, requires = "duration")]
+    then: Then,
+
+    /// Turn on SHADER even if it is already on
+    #[arg(long)]
+    force: bool,
+}
+
+#[derive(Debug, Clone, Copy, Default, ValueEnum)]
+enum Then {
+    /// The shader that was on before, or off if there was none
+    #[default]
+    Previous,
+    /// The scheduled shader, or off if there is no configuration file
+    Schedule,
 }
 
 impl MergeVarArg for On {}
@@ -34,10 +77,18 @@ impl MergeVarArg for On {}
 impl CommandExecute for On {
     #[tracing::instrument(level = "debug", skip_all)]
     fn execute(self, config: Option<&Config>) -> eyre::Result<ExitCode> {
-        let On { shader, var } = self;
+        let On {
+            shader,
+            var,
+            duration,
+            then,
+            force,
+        } = self;
 
         let data = Self::merge_into_data(var)?;
-        let shader = Resolver::with_cli_arg(&shader).resolve()?;
+        let shader = Resolver::with_cli_arg(&shader)
+            .resolve()
+            .shader_name_suggestion()?;
 
         let data = {
             let mut data = data;
@@ -47,7 +98,28 @@ impl CommandExecute for On {
             data
         };
 
-        shader.on(&data)?;
+        let revert = match (duration, then) {
+            (None, _) => None,
+            (Some(_), Then::Schedule) => Some(Revert::Schedule),
+            // Reverting from one timed shader to another would leave the other on indefinitely
+            (Some(_), Then::Previous) => Some(match TimedShader::read()? {
+                Some(timed) => timed.revert().clone(),
+                None => Revert::Previous(Shader::current(&Hyprland)?),
+            }),
+        };
+        TimedShader::cancel()?;
+
+        if force {
+            shader.on_remembering(&Hyprland, &data, true)?;
+        } else {
+            shader.on_if_changed_remembering(&Hyprland, &data, true)?;
+        }
+
+        if let (Some(duration), Some(revert)) = (duration, revert) {
+            let instance = Shader::current(&Hyprland)?.ok_or_eyre("shader was not turned on")?;
+            let until = now() + TimeDelta::from_std(duration)?;
+            TimedShader::new(until, instance.instance_path().to_owned(), revert).start()?;
+        }
 
         Ok(ExitCode::SUCCESS)
     }
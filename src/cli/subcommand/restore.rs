@@ -0,0 +1,71 @@
+use std::process::ExitCode;
+
+use clap::Parser;
+use tracing::{debug, warn};
+
+use crate::{
+    cli::CommandExecute, compositor::Hyprland, config::Config, events, lock::RuntimeLock,
+    shader::ShaderInstance,
+};
+
+const EXAMPLE_SECTION: &str = color_print::cstr!(
+    r#"<bold><underline>Examples:</underline></bold>
+  # in hyprland.conf, to keep the shader across config reloads
+  exec-once = hyprshade restore --watch
+"#
+);
+
+/// Sent by Hyprland after reloading its configuration, which clears the screen shader
+const CONFIG_RELOADED_EVENT: &str = "configreloaded";
+
+/**
+Turn the last shader back on
+
+Hyprland forgets the screen shader whenever its configuration is reloaded. This turns the shader
+that was last turned on back on, unless it was turned off since.
+*/
+#[derive(Debug, Parser)]
+#[command(after_long_help = EXAMPLE_SECTION)]
+pub struct Restore {
+    /// Keep running, and restore the shader every time Hyprland reloads its configuration
+    #[arg(long)]
+    watch: bool,
+}
+
+impl CommandExecute for Restore {
+    #[tracing::instrument(level = "debug", skip_all)]
+    fn execute(self, _config: Option<&Config>) -> eyre::Result<ExitCode> {
+        let Restore { watch } = self;
+
+        if !watch {
+            restore()?;
+            return Ok(ExitCode::SUCCESS);
+        }
+
+        // Connect first, so that a reload right after restoring isn't missed
+        let events = events::listen()?;
+        restore()?;
+        for event in events {
+            if event?.name == CONFIG_RELOADED_EVENT {
+                debug!("Hyprland reloaded its configuration");
+                if let Err(err) = restore() {
+                    warn!("Failed to restore shader: {err:?}");
+                }
+            }
+        }
+
+        debug!("Hyprland closed its event socket");
+        Ok(ExitCode::SUCCESS)
+    }
+}
+
+fn restore() -> eyre::Result<()> {
+    let _lock = RuntimeLock::acquire()?;
+    match ShaderInstance::last()? {
+        Some(instance) => Ok(instance.restore_remembering(&Hyprland, true)?),
+        None => {
+            debug!("No shader to restore");
+            Ok(())
+        }
+    }
+}
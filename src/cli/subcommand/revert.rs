@@ -0,0 +1,50 @@
+use std::process::ExitCode;
+
+use clap::Parser;
+use tracing::debug;
+
+use crate::{
+    cli::{CommandExecute, subcommand::Auto},
+    compositor::Hyprland,
+    config::Config,
+    shader::Shader,
+    time::now,
+    timed::{Revert as RevertTo, TimedShader},
+};
+
+/**
+Revert a shader turned on with `on --for` once its time is up
+
+Does nothing if the shader was replaced since, or if its time isn't up yet.
+*/
+#[derive(Debug, Parser)]
+#[command(hide = true)]
+pub struct Revert;
+
+impl CommandExecute for Revert {
+    #[tracing::instrument(level = "debug", skip_all)]
+    fn execute(self, config: Option<&Config>) -> eyre::Result<ExitCode> {
+        let Some(timed) = TimedShader::read()? else {
+            debug!("No timed shader to revert");
+            return Ok(ExitCode::SUCCESS);
+        };
+        if !timed.is_expired(&now()) {
+            debug!("Timed shader is on until {:?}", timed.until());
+            return Ok(ExitCode::SUCCESS);
+        }
+        TimedShader::cancel()?;
+
+        match timed.revert() {
+            RevertTo::Previous(Some(instance)) => {
+                instance.clone().restore_remembering(&Hyprland, true)?
+            }
+            RevertTo::Previous(None) => Shader::off_remembering(&Hyprland, true)?,
+            RevertTo::Schedule => match config {
+                Some(_) => return Auto::default().execute(config),
+                None => Shader::off_remembering(&Hyprland, true)?,
+            },
+        }
+
+        Ok(ExitCode::SUCCESS)
+    }
+}
@@ -0,0 +1,172 @@
+use std::{
+    env,
+    io::{self, BufRead, BufReader, Write},
+    iter,
+    os::unix::net::UnixStream,
+    path::PathBuf,
+    process::ExitCode,
+    sync::{Arc, Mutex, PoisonError},
+    thread,
+};
+
+use clap::Parser;
+use eyre::{Context, eyre};
+use tracing::{debug, info};
+
+use crate::{
+    cli::{Cli, CommandExecute, LiveConfig, instrumentation, subcommand::HyprshadeSubcommand},
+    config::Config,
+    control::{self, Request, Response},
+    index::ShaderIndex,
+    instance,
+};
+
+const EXAMPLE_SECTION: &str = color_print::cstr!(
+    r#"<bold><underline>Examples:</underline></bold>
+  # in hyprland.conf
+  exec-once = hyprshade serve
+
+  # turn on vibrance without running hyprshade
+  echo '{"args": ["on", "vibrance"]}' | socat - UNIX-CONNECT:$XDG_RUNTIME_DIR/hyprshade/$HYPRLAND_INSTANCE_SIGNATURE/control.sock
+"#
+);
+
+/**
+Run subcommands on behalf of other hyprshade processes
+
+Listens on $XDG_RUNTIME_DIR/hyprshade/<SIGNATURE>/control.sock, where <SIGNATURE> is that of the
+Hyprland instance. While it is running, adjust, auto, current, off, on, and toggle are run by it
+instead, which keeps the shader index and the configuration file loaded between calls. They still
+run in their own process when given --config or --no-cache. With --instance, they are run by the
+server of that instance, if there is one.
+
+Each line sent to the socket is a request such as {"args": ["on", "vibrance"]}, with the arguments
+that would be passed to hyprshade and optionally the "cwd" they are relative to, which is otherwise
+the directory hyprshade serve was started in. Each request is answered with a line such as
+{"status": "ok", "output": "..."}, or {"status": "error", "message": "...", "report": "..."}, along
+with any warnings it logged as "log".
+
+Requests may also give the "env" they must run with, i.e. which of HOME, HYPRSHADE_SHADERS_DIR,
+TZ, XDG_CONFIG_HOME, XDG_DATA_DIRS, and XDG_DATA_HOME are set and to what. Requests whose env differs
+from the server's are answered with {"status": "declined", "message": "..."}, and hyprshade then
+runs them in its own process.
+*/
+#[derive(Debug, Parser)]
+#[command(after_long_help = EXAMPLE_SECTION)]
+pub struct Serve {}
+
+impl CommandExecute for Serve {
+    #[tracing::instrument(level = "debug", skip_all)]
+    fn execute(self, config: Option<&Config>) -> eyre::Result<ExitCode> {
+        let listener = control::listen()?;
+        info!("Listening on {:?}", listener.local_addr()?);
+
+        let server = Arc::new(Mutex::new(Server::new(config.cloned())));
+        for stream in listener.incoming() {
+            let stream = stream.wrap_err("failed to accept connection")?;
+            let server = Arc::clone(&server);
+            // Clients may keep their connection open between requests, which mustn't keep others
+            // waiting
+            thread::spawn(move || {
+                if let Err(err) = serve(&server, &stream) {
+                    debug!("Closing connection: {err}");
+                }
+            });
+        }
+
+        Ok(ExitCode::SUCCESS)
+    }
+}
+
+fn serve(server: &Mutex<Server>, stream: &UnixStream) -> io::Result<()> {
+    let mut writer = stream;
+    for line in BufReader::new(stream).lines() {
+        let response = server
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .handle(&line?);
+        let mut response = serde_json::to_vec(&response)?;
+        response.push(b'\n');
+        writer.write_all(&response)?;
+    }
+    Ok(())
+}
+
+struct Server {
+    config: LiveConfig,
+    /// Directory the server was started in, which requests without a `cwd` are relative to
+    cwd: Option<PathBuf>,
+}
+
+impl Server {
+    fn new(config: Option<Config>) -> Self {
+        Server {
+            config: LiveConfig::new(config),
+            cwd: env::current_dir().ok(),
+        }
+    }
+
+    fn handle(&mut self, line: &str) -> Response {
+        let request = serde_json::from_str::<Request>(line).wrap_err("invalid request");
+        // Shader directories and the time zone come from the environment, which can't be changed
+        // for a single request
+        let env = request
+            .as_ref()
+            .ok()
+            .and_then(|request| request.env.as_ref());
+        if env.is_some_and(|env| *env != Request::current_env()) {
+            return Response::Declined {
+                message: "hyprshade serve runs with different environment variables".to_owned(),
+            };
+        }
+        let (result, log) =
+            instrumentation::capture(|| request.and_then(|request| self.run(request)));
+        Response::new(result, log)
+    }
+
+    /// Runs the subcommand as its own process would, and returns what it printed.
+    #[tracing::instrument(level = "debug", skip(self))]
+    fn run(&mut self, request: Request) -> eyre::Result<String> {
+        let Request { args, cwd, .. } = request;
+        let cli = Cli::try_parse_from(iter::once(env!("CARGO_PKG_NAME").to_owned()).chain(args))?;
+        self.config.reload()?;
+
+        if !cli.command.is_forwarded() {
+            return Err(eyre!("hyprshade serve doesn't run this subcommand"));
+        }
+        if cli.no_cache {
+            return Err(eyre!(
+                "hyprshade serve doesn't run subcommands with --no-cache"
+            ));
+        }
+        if cli.instance.is_some() && cli.instance.as_deref() != instance::signature() {
+            return Err(eyre!(
+                "hyprshade serve only runs subcommands for its own Hyprland instance"
+            ));
+        }
+        let config_path = self.config.get().map(Config::path);
+        if cli.config.is_some() && cli.config.as_deref() != config_path {
+            return Err(eyre!(
+                "hyprshade serve only runs subcommands with its own configuration file"
+            ));
+        }
+
+        // Rather than the directory of the previous request
+        if let Some(cwd) = cwd.as_ref().or(self.cwd.as_ref()) {
+            env::set_current_dir(cwd)
+                .wrap_err_with(|| format!("failed to change directory to {cwd:?}"))?;
+        }
+        if let Some(config) = self.config.get() {
+            config.add_shader_dirs();
+        }
+        ShaderIndex::refresh();
+
+        if let HyprshadeSubcommand::Current(current) = &cli.command {
+            let mut output = Vec::new();
+            current.write(&mut output)?;
+            return Ok(String::from_utf8_lossy(&output).into_owned());
+        }
+        cli.execute(self.config.get())?;
+        Ok(String::new())
+    }
+}
@@ -0,0 +1,46 @@
+mod add;
+use add::Add;
+mod eject;
+use eject::Eject;
+mod list;
+use list::List;
+mod remove;
+use remove::Remove;
+
+use std::process::ExitCode;
+
+use clap::Parser;
+
+use crate::{cli::CommandExecute, config::Config};
+
+/**
+Manage shader packs and builtin shaders
+
+Packs are installed into their own directory in the shaders directory of the Hyprshade
+configuration directory, where their shaders can be turned on by name like any other shader.
+*/
+#[derive(Debug, Parser)]
+pub struct Shader {
+    #[command(subcommand)]
+    command: ShaderSubcommand,
+}
+
+#[derive(Debug, clap::Subcommand)]
+enum ShaderSubcommand {
+    Add(Add),
+    Eject(Eject),
+    List(List),
+    Remove(Remove),
+}
+
+impl CommandExecute for Shader {
+    #[tracing::instrument(level = "trace", skip_all)]
+    fn execute(self, config: Option<&Config>) -> eyre::Result<ExitCode> {
+        match self.command {
+            ShaderSubcommand::Add(add) => add.execute(config),
+            ShaderSubcommand::Eject(eject) => eject.execute(config),
+            ShaderSubcommand::List(list) => list.execute(config),
+            ShaderSubcommand::Remove(remove) => remove.execute(config),
+        }
+    }
+}
@@ -0,0 +1,73 @@
+use std::{path::PathBuf, process::ExitCode};
+
+use clap::Parser;
+use color_eyre::{Section, SectionExt};
+use tracing::warn;
+
+use crate::{
+    cli::CommandExecute,
+    config::Config,
+    constants::PACK_MANIFEST_FILE_NAME,
+    pack::{self, Pack},
+};
+
+const EXAMPLE_SECTION: &str = color_print::cstr!(
+    r#"<bold><underline>Examples:</underline></bold>
+  # install all shaders in a directory as a pack
+  hyprshade shader add ./team-shaders
+
+  # install a pack from a tarball, replacing an older version
+  hyprshade shader add team-shaders-1.2.0.tar.gz --force
+"#
+);
+
+/**
+Install a shader pack from a directory or tarball
+
+If the pack contains a hyprshade-pack.toml manifest, its name and list of shaders are used.
+Otherwise, the pack is named after the directory or tarball and includes every shader file in it.
+*/
+#[derive(Debug, Parser)]
+#[command(after_long_help = EXAMPLE_SECTION)]
+pub struct Add {
+    /// Directory or tarball (optionally gzipped) containing the pack
+    #[arg(value_name = "DIR|TARBALL")]
+    source: PathBuf,
+
+    /// Replace an installed pack with the same name, and install even if its shaders collide with
+    /// existing shaders
+    #[arg(long)]
+    force: bool,
+}
+
+impl CommandExecute for Add {
+    #[tracing::instrument(level = "debug", skip_all)]
+    fn execute(self, _config: Option<&Config>) -> eyre::Result<ExitCode> {
+        let Add { source, force } = self;
+
+        let (_pack, collisions) = Pack::install(&source, force).map_err(|err| match err {
+            pack::Error::AlreadyInstalled(_) => eyre::Report::new(err)
+                .suggestion("Use --force to replace it, or `hyprshade shader remove` it first"),
+            pack::Error::Collisions { ref collisions, .. } => {
+                let section = collisions
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                eyre::Report::new(err)
+                    .section(section.header("Collisions:"))
+                    .suggestion(format!(
+                        "Rename the shaders in the pack (listed in {PACK_MANIFEST_FILE_NAME}), \
+                        or use --force to install anyway"
+                    ))
+            }
+            err => eyre::Report::new(err),
+        })?;
+
+        for collision in collisions {
+            warn!("{collision}");
+        }
+
+        Ok(ExitCode::SUCCESS)
+    }
+}
@@ -0,0 +1,118 @@
+use std::{fs, process::ExitCode};
+
+use clap::Parser;
+use clap_complete::ArgValueCandidates;
+use color_eyre::Section;
+use eyre::{Context, OptionExt, eyre};
+use tracing::warn;
+
+use crate::{
+    builtin::BuiltinShader,
+    cli::{CommandExecute, arg::complete},
+    config::Config,
+    dirs::user_shader_dir,
+    metadata::sidecar_path,
+    shader::TEMPLATE_EXTENSION,
+};
+
+const EXAMPLE_SECTION: &str = color_print::cstr!(
+    r#"<bold><underline>Examples:</underline></bold>
+  # copy vibrance into the user shader directory, where it replaces the builtin
+  hyprshade shader eject vibrance
+
+  # copy vibrance under a different name, keeping the builtin
+  hyprshade shader eject vibrance --as my-vibrance
+"#
+);
+
+/**
+Copy a builtin shader into the user shader directory so it can be edited
+
+Alongside the shader, a TOML file describing its variables is written. The defaults in it are used
+when the shader is turned on, and may be edited as well.
+
+Prints the path of the ejected shader.
+*/
+#[derive(Debug, Parser)]
+#[command(after_long_help = EXAMPLE_SECTION)]
+pub struct Eject {
+    /// Name of the builtin shader
+    #[arg(value_name = "NAME", add = ArgValueCandidates::new(complete::builtin_shader))]
+    name: String,
+
+    /// Name of the ejected copy (defaults to NAME)
+    #[arg(long = "as", value_name = "NEW_NAME")]
+    new_name: Option<String>,
+
+    /// Overwrite existing files
+    #[arg(long)]
+    force: bool,
+}
+
+impl CommandExecute for Eject {
+    #[tracing::instrument(level = "debug", skip_all)]
+    fn execute(self, _config: Option<&Config>) -> eyre::Result<ExitCode> {
+        let Eject {
+            name,
+            new_name,
+            force,
+        } = self;
+
+        let builtin_shader = BuiltinShader::get(&name)
+            .ok_or_else(|| eyre!("builtin shader named {name:?} not found"))
+            .suggestion("Use `hyprshade ls` to list shaders")?;
+        if builtin_shader.is_baked() {
+            return Err(eyre!("builtin shader {name:?} can't be ejected"))
+                .note("It is generated from data computed when it is turned on");
+        }
+
+        let new_name = new_name.unwrap_or(name);
+        if new_name.is_empty() || new_name.contains(['/', '.']) {
+            return Err(eyre!("invalid shader name {new_name:?}"))
+                .note("Shader names may not be empty or contain '/' or '.'");
+        }
+
+        let dir = user_shader_dir();
+        let file_name = if builtin_shader.is_template() {
+            format!("{new_name}.glsl.{TEMPLATE_EXTENSION}")
+        } else {
+            format!("{new_name}.glsl")
+        };
+        let shader_path = dir.join(file_name);
+        let metadata_path = sidecar_path(&shader_path).ok_or_eyre("invalid shader path")?;
+
+        if !force {
+            if let Some(path) = [&shader_path, &metadata_path]
+                .into_iter()
+                .find(|p| p.exists())
+            {
+                return Err(eyre!("{path:?} already exists"))
+                    .suggestion("Use --force to overwrite it");
+            }
+        }
+
+        let metadata = format!(
+            "# Ejected from the builtin shader {:?}.\n\
+            # The defaults of variables are used when the shader is turned on.\n\n{}",
+            builtin_shader.name(),
+            toml::to_string(&builtin_shader.metadata_toml())?,
+        );
+
+        fs::create_dir_all(&dir).wrap_err_with(|| format!("could not create {dir:?}"))?;
+        fs::write(&shader_path, builtin_shader.contents())
+            .wrap_err_with(|| format!("could not write {shader_path:?}"))?;
+        fs::write(&metadata_path, metadata)
+            .wrap_err_with(|| format!("could not write {metadata_path:?}"))?;
+
+        if let Some(shadowed) = BuiltinShader::get(&new_name) {
+            warn!(
+                "{shader_path:?} now shadows the builtin shader {:?} when turned on by name",
+                shadowed.name()
+            );
+        }
+
+        println!("{}", shader_path.display());
+
+        Ok(ExitCode::SUCCESS)
+    }
+}
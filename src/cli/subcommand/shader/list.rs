@@ -0,0 +1,30 @@
+use std::process::ExitCode;
+
+use clap::Parser;
+
+use crate::{cli::CommandExecute, config::Config, pack::Pack};
+
+/**
+List installed packs and their shaders
+
+Use `hyprshade ls` to list every shader that can be turned on by name.
+*/
+#[derive(Debug, Parser)]
+pub struct List {}
+
+impl CommandExecute for List {
+    #[tracing::instrument(level = "debug", skip_all)]
+    fn execute(self, _config: Option<&Config>) -> eyre::Result<ExitCode> {
+        for pack in Pack::installed()? {
+            match pack.version() {
+                Some(version) => println!("{} {version}", pack.name()),
+                None => println!("{}", pack.name()),
+            }
+            for name in pack.shader_names() {
+                println!("  {}", name.to_string_lossy());
+            }
+        }
+
+        Ok(ExitCode::SUCCESS)
+    }
+}
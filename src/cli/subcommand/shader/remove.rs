@@ -0,0 +1,38 @@
+use std::process::ExitCode;
+
+use clap::Parser;
+use clap_complete::ArgValueCandidates;
+use color_eyre::Section;
+use eyre::eyre;
+
+use crate::{
+    cli::{CommandExecute, arg::complete},
+    config::Config,
+    pack::Pack,
+};
+
+/**
+Remove an installed shader pack
+*/
+#[derive(Debug, Parser)]
+pub struct Remove {
+    /// Name of the pack
+    #[arg(value_name = "PACK", add = ArgValueCandidates::new(complete::installed_pack))]
+    name: String,
+}
+
+impl CommandExecute for Remove {
+    #[tracing::instrument(level = "debug", skip_all)]
+    fn execute(self, _config: Option<&Config>) -> eyre::Result<ExitCode> {
+        let Remove { name } = self;
+
+        let Some(pack) = Pack::get(&name)? else {
+            return Err(eyre!("pack {name:?} is not installed"))
+                .suggestion("Use `hyprshade shader list` to list installed packs");
+        };
+
+        pack.remove()?;
+
+        Ok(ExitCode::SUCCESS)
+    }
+}
@@ -1,6 +1,7 @@
 use std::{cell::LazyCell, process::ExitCode};
 
 use clap::Parser;
+use clap_complete::ArgValueCompleter;
 use color_eyre::Section;
 use const_format::{concatcp, formatcp};
 use eyre::{OptionExt, eyre};
@@ -9,10 +10,12 @@ use crate::{
     cli::{
         CommandExecute,
         arg::{
+            complete,
             help::{SHADER_HELP, SHADER_HELP_LONG as SHADER_HELP_LONG_SOURCE},
             var::{MergeVarArg, VarArg, VarArgParser},
         },
     },
+    compositor::Hyprland,
     config::Config,
     constants::README_CONFIGURATION,
     resolver::{self, Resolver},
@@ -20,7 +23,8 @@ use crate::{
     shader::Shader,
     template::MergeDeep,
     time::now,
-    util::ConfigSection,
+    timed::TimedShader,
+    util::{ConfigSection, ShaderNameSuggestion, similar_names_suggestion},
 };
 
 const EXAMPLE_SECTION: &str = color_print::cstr!(
@@ -63,15 +67,15 @@ equal to it, or using the default shader otherwise.
 #[derive(Debug, Parser)]
 #[command(after_help = AFTER_HELP, after_long_help = AFTER_LONG_HELP)]
 pub struct Toggle {
-    #[arg(help = SHADER_HELP, long_help = SHADER_HELP_LONG)]
+    #[arg(help = SHADER_HELP, long_help = SHADER_HELP_LONG, add = ArgValueCompleter::new(complete::shader))]
     shader: Option<String>,
 
     /// Configuration variable used in rendering SHADER (may be specified multiple times)
-    #[arg(long, value_name = "KEY=VALUE", value_parser = VarArgParser)]
+    #[arg(long, value_name = "KEY=VALUE", value_parser = VarArgParser, add = ArgValueCompleter::new(complete::var))]
     var: Vec<VarArg>,
 
     /// Specify fallback shader
-    #[arg(long, group = "fallback_args")]
+    #[arg(long, group = "fallback_args", add = ArgValueCompleter::new(complete::shader))]
     fallback: Option<String>,
 
     /// Use default shader as fallback
@@ -85,7 +89,7 @@ pub struct Toggle {
     /// Configuration variable used in rendering fallback shader (may be specified multiple times)
     ///
     /// Applies to `--fallback`, `--fallback-default`, and `--fallback-auto`
-    #[arg(long, value_name = "KEY=VALUE", value_parser = VarArgParser, requires = "fallback_args")]
+    #[arg(long, value_name = "KEY=VALUE", value_parser = VarArgParser, requires = "fallback_args", add = ArgValueCompleter::new(complete::var_fallback))]
     var_fallback: Vec<VarArg>,
 }
 
@@ -119,7 +123,7 @@ impl CommandExecute for Toggle {
         let scheduled_shader_cell: LazyCell<ScheduledShaderResult, _> = LazyCell::new(|| {
             config.map_or(ScheduledShaderResult::NoConfig, |config| {
                 Schedule::with_config(config)
-                    .scheduled_shader(&now.time())
+                    .scheduled_shader(&now)
                     .map_or_else(
                         ScheduledShaderResult::ResolverError,
                         ScheduledShaderResult::Shader,
@@ -148,8 +152,8 @@ impl CommandExecute for Toggle {
                     err = with_readme_suggestion(err);
                     Err(err)
                 }
-                ScheduledShaderResult::ResolverError(err) => {
-                    let mut err = eyre!("{}", err)
+                ScheduledShaderResult::ResolverError(source) => {
+                    let mut err = eyre!("{}", source)
                         .wrap_err("resolving shader in config")
                         .config_section(config.expect("config file should exist").path());
                     err = match &cause {
@@ -160,6 +164,9 @@ impl CommandExecute for Toggle {
                             "Tried to resolve scheduled shader because of --fallback-auto",
                         ),
                     };
+                    if let Some(suggestion) = similar_names_suggestion(&source.similar_names()) {
+                        err = err.suggestion(suggestion);
+                    }
                     err = err.suggestion(
                         "Change the shader name in your configuration, or make sure a shader by that name exists",
                     );
@@ -213,8 +220,8 @@ impl CommandExecute for Toggle {
                     err = with_readme_suggestion(err);
                     Err(err)
                 }
-                DefaultShaderResult::ResolverError(err) => {
-                    let mut err = eyre!("{}", err)
+                DefaultShaderResult::ResolverError(source) => {
+                    let mut err = eyre!("{}", source)
                         .wrap_err("resolving default shader in config")
                         .config_section(config.expect("config file should exist").path());
                     err = match &cause {
@@ -223,6 +230,9 @@ impl CommandExecute for Toggle {
                             err.note("Tried to resolve default shader because of --fallback-auto")
                         }
                     };
+                    if let Some(suggestion) = similar_names_suggestion(&source.similar_names()) {
+                        err = err.suggestion(suggestion);
+                    }
                     err = err
                         .suggestion(
                             "Change the shader name in your configuration, or make sure a shader by that name exists",
@@ -239,13 +249,21 @@ impl CommandExecute for Toggle {
         let shader_data = Self::merge_into_data(var)?;
 
         let shader: Option<Shader> = match &shader {
-            Some(shader) => Some(Resolver::with_cli_arg(shader).resolve()?),
+            Some(shader) => Some(
+                Resolver::with_cli_arg(shader)
+                    .resolve()
+                    .shader_name_suggestion()?,
+            ),
             None => scheduled_shader(ScheduledShaderCause::OmittedShader)?,
         };
 
         let fallback = match (&fallback, fallback_default, fallback_auto) {
             (None, false, false) => None,
-            (Some(fallback), false, false) => Some(Resolver::with_cli_arg(fallback).resolve()?),
+            (Some(fallback), false, false) => Some(
+                Resolver::with_cli_arg(fallback)
+                    .resolve()
+                    .shader_name_suggestion()?,
+            ),
             (None, true, false) => Some(
                 default_shader(DefaultShaderCause::FallbackDefault).and_then(|shader| {
                     let result = shader
@@ -270,7 +288,9 @@ impl CommandExecute for Toggle {
             }
         };
 
-        let current_shader = Shader::current()?.map(Shader::try_from).transpose()?;
+        let current_shader = Shader::current(&Hyprland)?
+            .map(Shader::try_from)
+            .transpose()?;
 
         let (designated_shader, designated_data) = if shader == current_shader {
             (fallback, fallback_data)
@@ -278,6 +298,7 @@ impl CommandExecute for Toggle {
             (shader, shader_data)
         };
 
+        TimedShader::cancel()?;
         if let Some(designated_shader) = designated_shader {
             let designated_data = {
                 let mut designated_data = designated_data;
@@ -287,9 +308,9 @@ impl CommandExecute for Toggle {
                 designated_data
             };
 
-            designated_shader.on(&designated_data)?;
+            designated_shader.on_remembering(&Hyprland, &designated_data, true)?;
         } else {
-            Shader::off()?;
+            Shader::off_remembering(&Hyprland, true)?;
         }
 
         Ok(ExitCode::SUCCESS)
@@ -0,0 +1,32 @@
+use std::process::ExitCode;
+
+use clap::Parser;
+use tracing::debug;
+
+use crate::{
+    cli::{CommandExecute, subcommand::Auto},
+    config::Config,
+    inhibit::Inhibit,
+};
+
+/**
+End an inhibit and resume the schedule
+
+If there is a configuration file, the scheduled shader is turned on right away.
+*/
+#[derive(Debug, Parser)]
+pub struct Uninhibit;
+
+impl CommandExecute for Uninhibit {
+    #[tracing::instrument(level = "debug", skip_all)]
+    fn execute(self, config: Option<&Config>) -> eyre::Result<ExitCode> {
+        if !Inhibit::end()? {
+            debug!("Not inhibited");
+        }
+
+        match config {
+            Some(_) => Auto::default().execute(config),
+            None => Ok(ExitCode::SUCCESS),
+        }
+    }
+}
@@ -0,0 +1,65 @@
+use std::process::ExitCode;
+
+use clap::Parser;
+use clap_complete::ArgValueCompleter;
+use color_eyre::Section;
+use eyre::eyre;
+
+use crate::{
+    cli::{CommandExecute, arg::complete},
+    config::Config,
+    resolver::{Candidate, Resolver, similar_names},
+    util::similar_names_suggestion,
+};
+
+/**
+Show which shader a name resolves to
+
+Prints the file (or builtin) that is turned on for NAME, followed by every other shader with the
+same name that it takes precedence over.
+*/
+#[derive(Debug, Parser)]
+pub struct Which {
+    /// Name of the shader (e.g. `blue-light-filter`)
+    #[arg(value_name = "NAME", add = ArgValueCompleter::new(complete::shader))]
+    name: String,
+}
+
+impl CommandExecute for Which {
+    #[tracing::instrument(level = "debug", skip_all)]
+    fn execute(self, _config: Option<&Config>) -> eyre::Result<ExitCode> {
+        let Which { name } = self;
+
+        let candidates = Resolver::with_cli_arg(&name).candidates();
+        let Some((winner, others)) = candidates.split_first() else {
+            let mut err = eyre!("shader named {name:?} not found");
+            if let Some(suggestion) = similar_names_suggestion(&similar_names(&name)) {
+                err = err.suggestion(suggestion);
+            }
+            return Err(err.suggestion("Use `hyprshade ls` to list shaders"));
+        };
+
+        match winner {
+            Candidate::File { path, .. } => println!("{}", path.display()),
+            Candidate::Builtin(builtin) => println!("{} (builtin)", builtin.name()),
+        }
+
+        let winner_dir = match winner {
+            Candidate::File { dir, .. } => Some(dir),
+            Candidate::Builtin(_) => None,
+        };
+        for other in others {
+            match other {
+                Candidate::File { dir, path } if Some(dir) == winner_dir => {
+                    println!("  ambiguous with {}", path.display())
+                }
+                Candidate::File { path, .. } => println!("  shadows {}", path.display()),
+                Candidate::Builtin(builtin) => {
+                    println!("  shadows builtin shader {:?}", builtin.name())
+                }
+            }
+        }
+
+        Ok(ExitCode::SUCCESS)
+    }
+}
@@ -0,0 +1,75 @@
+//! Where shaders take effect
+
+use std::path::{Path, PathBuf};
+
+use crate::hyprctl;
+
+/// Loads screen shaders, so that [`Shader`](crate::shader::Shader) can turn them on and off.
+///
+/// [`Hyprland`] is what the command line tool uses. Other implementations can apply shaders
+/// elsewhere, or record what would have been applied.
+///
+/// ```no_run
+/// # use std::{cell::RefCell, convert::Infallible, path::{Path, PathBuf}};
+/// # use hyprshade::{Compositor, Resolver, TemplateDataMap};
+/// #[derive(Default)]
+/// struct Recorder(RefCell<Option<PathBuf>>);
+///
+/// impl Compositor for Recorder {
+///     type Error = Infallible;
+///
+///     fn screen_shader(&self) -> Result<Option<PathBuf>, Infallible> {
+///         Ok(self.0.borrow().clone())
+///     }
+///
+///     fn set_screen_shader(&self, path: &Path) -> Result<(), Infallible> {
+///         *self.0.borrow_mut() = Some(path.to_owned());
+///         Ok(())
+///     }
+///
+///     fn clear_screen_shader(&self) -> Result<(), Infallible> {
+///         *self.0.borrow_mut() = None;
+///         Ok(())
+///     }
+/// }
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let recorder = Recorder::default();
+/// let shader = Resolver::with_name("grayscale").resolve()?;
+/// shader.on(&recorder, &TemplateDataMap::new())?;
+/// assert!(recorder.screen_shader()?.is_some());
+/// # Ok(())
+/// # }
+/// ```
+pub trait Compositor {
+    type Error: std::error::Error + Send + Sync + 'static;
+
+    /// Returns the path of the loaded screen shader, or [`None`] if there is none.
+    fn screen_shader(&self) -> Result<Option<PathBuf>, Self::Error>;
+
+    /// Loads the screen shader at `path`, which is absolute.
+    fn set_screen_shader(&self, path: &Path) -> Result<(), Self::Error>;
+
+    /// Unloads the screen shader, if any.
+    fn clear_screen_shader(&self) -> Result<(), Self::Error>;
+}
+
+/// Applies shaders to the running Hyprland instance through `hyprctl`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Hyprland;
+
+impl Compositor for Hyprland {
+    type Error = hyprctl::Error;
+
+    fn screen_shader(&self) -> Result<Option<PathBuf>, Self::Error> {
+        hyprctl::shader::get()
+    }
+
+    fn set_screen_shader(&self, path: &Path) -> Result<(), Self::Error> {
+        hyprctl::shader::set(path)
+    }
+
+    fn clear_screen_shader(&self) -> Result<(), Self::Error> {
+        hyprctl::shader::clear()
+    }
+}
@@ -6,8 +6,13 @@ use std::{
 
 use serde::{Deserialize, Serialize};
 
-use crate::template::TemplateDataMap;
+use crate::{
+    constants::{HYPRLAND_CONFIG_DIR, HYPRSHADE_CONFIG_DIR},
+    dirs,
+    template::TemplateDataMap,
+};
 
+/// Parsed configuration file, with the schedule and the variables of each shader
 #[derive(Debug, Clone)]
 pub struct Config {
     config: ConfigDocument,
@@ -17,10 +22,17 @@ pub struct Config {
 #[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
 #[cfg_attr(feature = "compat", serde(from = "CompatConfig"))]
 pub struct ConfigDocument {
+    /// Extra directories to search for shaders by name, relative to the config file
+    #[serde(default)]
+    pub shader_dirs: Vec<PathBuf>,
+    /// Time zone the schedule is in, instead of the system's (e.g. `Europe/Berlin`)
+    #[serde(default)]
+    pub timezone: Option<chrono_tz::Tz>,
     #[serde(default)]
     pub shader: Vec<Shader>,
 }
 
+/// A `[[shader]]` entry of the configuration file
 #[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
 pub struct Shader {
     pub name: String,
@@ -35,6 +47,7 @@ pub struct Shader {
 }
 
 impl Config {
+    /// Reads the config file at `path`.
     pub fn read<P: AsRef<Path>>(path: P) -> Result<Self, ConfigReadError> {
         fn _read(path: &Path) -> Result<Config, ConfigReadError> {
             let contents = fs::read_to_string(path).map_err(|source| ConfigReadError::Io {
@@ -56,10 +69,48 @@ impl Config {
         _read(path.as_ref())
     }
 
+    /// Reads the first config file found in the default locations, which are
+    /// `~/.config/hypr/hyprshade.toml` and `~/.config/hyprshade/config.toml`.
+    pub fn find() -> Result<Option<Self>, ConfigReadError> {
+        for path in &[
+            HYPRLAND_CONFIG_DIR.to_owned().join("hyprshade.toml"),
+            HYPRSHADE_CONFIG_DIR.to_owned().join("config.toml"),
+        ] {
+            match Config::read(path) {
+                Ok(config) => return Ok(Some(config)),
+                Err(ConfigReadError::Io { .. }) => continue,
+                Err(err @ ConfigReadError::Parse { .. }) => return Err(err),
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Makes shaders in [`Config::shader_dirs`] resolvable by name, in place of those of a config
+    /// added before.
+    pub fn add_shader_dirs(&self) {
+        dirs::set_config_shader_dirs(self.shader_dirs());
+    }
+
     pub fn path(&self) -> &Path {
         &self.path
     }
 
+    /// Extra directories to search for shaders by name, with relative paths resolved against the
+    /// directory of the config file.
+    pub fn shader_dirs(&self) -> Vec<PathBuf> {
+        let base = self.path.parent().unwrap_or(Path::new(""));
+        self.config
+            .shader_dirs
+            .iter()
+            .map(|dir| base.join(dir))
+            .collect()
+    }
+
+    pub fn timezone(&self) -> Option<chrono_tz::Tz> {
+        self.config.timezone
+    }
+
     pub fn all_shaders(&self) -> &[Shader] {
         &self.config.shader
     }
@@ -68,6 +119,7 @@ impl Config {
         self.config.shader.iter().find(|shader| shader.name == name)
     }
 
+    /// Variables set for the shader named `name`
     pub fn data(&self, name: &str) -> Option<&TemplateDataMap> {
         self.shader(name).map(|s| &s.config)
     }
@@ -88,6 +140,10 @@ impl FromStr for ConfigDocument {
 #[cfg(feature = "compat")]
 #[derive(Debug, Deserialize)]
 pub struct CompatConfig {
+    #[serde(default)]
+    pub shader_dirs: Vec<PathBuf>,
+    #[serde(default)]
+    pub timezone: Option<chrono_tz::Tz>,
     #[serde(alias = "shades", alias = "shaders", default)]
     pub shader: Vec<Shader>,
 }
@@ -95,8 +151,16 @@ pub struct CompatConfig {
 #[cfg(feature = "compat")]
 impl From<CompatConfig> for ConfigDocument {
     fn from(value: CompatConfig) -> Self {
-        let CompatConfig { shader } = value;
-        Self { shader }
+        let CompatConfig {
+            shader_dirs,
+            timezone,
+            shader,
+        } = value;
+        Self {
+            shader_dirs,
+            timezone,
+            shader,
+        }
     }
 }
 
@@ -4,6 +4,12 @@ use directories::ProjectDirs;
 
 pub const HYPRSHADE_CONFIG_FILE_ENV: &str = "HYPRSHADE_CONFIG_FILE";
 pub const HYPRSHADE_SHADERS_DIR_ENV: &str = "HYPRSHADE_SHADERS_DIR";
+pub const HYPRLAND_INSTANCE_SIGNATURE_ENV: &str = "HYPRLAND_INSTANCE_SIGNATURE";
+
+/// Set by the script from `hyprshade completions` to request completions instead of running
+pub const COMPLETE_ENV: &str = "COMPLETE";
+
+pub const PACK_MANIFEST_FILE_NAME: &str = "hyprshade-pack.toml";
 
 static HYPRSHADE_PROJECT_DIRS: LazyLock<ProjectDirs> = LazyLock::new(|| {
     ProjectDirs::from("", "", &env!("CARGO_PKG_NAME").replace('-', "_"))
@@ -12,17 +18,29 @@ static HYPRSHADE_PROJECT_DIRS: LazyLock<ProjectDirs> = LazyLock::new(|| {
 
 pub static HYPRSHADE_CONFIG_DIR: LazyLock<&'static Path> =
     LazyLock::new(|| HYPRSHADE_PROJECT_DIRS.config_dir());
+pub static HYPRSHADE_DATA_DIR: LazyLock<&'static Path> =
+    LazyLock::new(|| HYPRSHADE_PROJECT_DIRS.data_dir());
 pub static HYPRSHADE_RUNTIME_DIR: LazyLock<&'static Path> = LazyLock::new(|| {
     HYPRSHADE_PROJECT_DIRS
         .runtime_dir()
         .expect("failed to get XDG_RUNTIME_DIR")
 });
 
+/// Like [`HYPRSHADE_RUNTIME_DIR`], for callers that can do without a runtime directory.
+pub fn hyprshade_runtime_dir() -> Option<&'static Path> {
+    HYPRSHADE_PROJECT_DIRS.runtime_dir()
+}
+
 static HYPRLAND_PROJECT_DIRS: LazyLock<ProjectDirs> =
     LazyLock::new(|| ProjectDirs::from("", "", "hypr").expect("failed to get HOME"));
 
 pub static HYPRLAND_CONFIG_DIR: LazyLock<&'static Path> =
     LazyLock::new(|| HYPRLAND_PROJECT_DIRS.config_dir());
 
+/// Where Hyprland keeps the sockets of each instance, or [`None`] without a runtime directory.
+pub fn hyprland_runtime_dir() -> Option<&'static Path> {
+    HYPRLAND_PROJECT_DIRS.runtime_dir()
+}
+
 pub const README_CONFIGURATION: &str = concat!(env!("CARGO_PKG_REPOSITORY"), "#configuration");
 pub const README_SCHEDULING: &str = concat!(env!("CARGO_PKG_REPOSITORY"), "#scheduling");
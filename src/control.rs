@@ -0,0 +1,154 @@
+//! Control socket of `hyprshade serve`, which runs subcommands on behalf of other hyprshade
+//! processes
+//!
+//! Each line sent to the socket is a [`Request`] in JSON, answered by a line with a [`Response`].
+
+use std::{
+    collections::BTreeMap,
+    env, fs,
+    io::{self, BufRead, BufReader, Write},
+    os::unix::net::{UnixListener, UnixStream},
+    path::PathBuf,
+};
+
+use eyre::{Context, eyre};
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+use crate::{
+    constants::{HYPRSHADE_SHADERS_DIR_ENV, hyprshade_runtime_dir},
+    instance,
+    lock::RuntimeLock,
+    util::make_runtime_path,
+};
+
+const SOCKET_FILE_NAME: &str = "control.sock";
+
+/// Environment variables that change what subcommands do, so that requests are only run if the
+/// server has the same ones
+pub const ENV: &[&str] = &[
+    HYPRSHADE_SHADERS_DIR_ENV,
+    "HOME",
+    "XDG_CONFIG_HOME",
+    "XDG_DATA_HOME",
+    "XDG_DATA_DIRS",
+    "TZ",
+    "__HYPRSHADE_MOCK_TIME",
+];
+
+/// Subcommand to run, e.g. `{"args": ["on", "vibrance"]}`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Request {
+    /// Arguments as they would be passed to `hyprshade`, starting with the subcommand
+    pub args: Vec<String>,
+    /// Directory that relative paths in `args` are relative to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cwd: Option<PathBuf>,
+    /// Those of [`ENV`] that are set, if the request must run with them
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub env: Option<BTreeMap<String, String>>,
+}
+
+impl Request {
+    /// Returns the variables of [`ENV`] that are set in this process.
+    pub fn current_env() -> BTreeMap<String, String> {
+        ENV.iter()
+            .filter_map(|&key| Some((key.to_owned(), env::var(key).ok()?)))
+            .collect()
+    }
+}
+
+/// Outcome of a [`Request`]
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum Response {
+    Ok {
+        /// What the subcommand printed
+        output: String,
+        /// Warnings the subcommand logged, as `hyprshade` prints them
+        #[serde(default, skip_serializing_if = "String::is_empty")]
+        log: String,
+    },
+    Error {
+        /// What went wrong, without causes
+        message: String,
+        /// The error as `hyprshade` prints it, with causes and suggestions
+        report: String,
+        /// Warnings the subcommand logged before failing, as `hyprshade` prints them
+        #[serde(default, skip_serializing_if = "String::is_empty")]
+        log: String,
+    },
+    /// The request wasn't run, and should be run by the client instead
+    Declined {
+        /// Why it wasn't run
+        message: String,
+    },
+}
+
+impl Response {
+    /// Response to a request that was run, with what it printed or how it failed, and what it
+    /// logged
+    pub fn new(result: eyre::Result<String>, log: String) -> Self {
+        match result {
+            Ok(output) => Response::Ok { output, log },
+            Err(err) => Response::Error {
+                message: err.to_string(),
+                report: format!("{err:?}"),
+                log,
+            },
+        }
+    }
+}
+
+/// Sends `request` to `hyprshade serve`, or returns [`None`] if it isn't running.
+#[tracing::instrument(level = "debug", skip_all)]
+pub fn send(request: &Request) -> eyre::Result<Option<Response>> {
+    let Some(path) =
+        hyprshade_runtime_dir().map(|dir| instance::namespace(dir).join(SOCKET_FILE_NAME))
+    else {
+        return Ok(None);
+    };
+    let stream = match UnixStream::connect(&path) {
+        Ok(stream) => stream,
+        Err(err) => {
+            debug!("Not connecting to {path:?}: {err}");
+            return Ok(None);
+        }
+    };
+
+    let mut request = serde_json::to_vec(request)?;
+    request.push(b'\n');
+    (&stream)
+        .write_all(&request)
+        .wrap_err_with(|| format!("failed to send request to {path:?}"))?;
+
+    let mut response = String::new();
+    BufReader::new(&stream)
+        .read_line(&mut response)
+        .wrap_err_with(|| format!("failed to read response from {path:?}"))?;
+    if response.is_empty() {
+        return Err(eyre!("hyprshade serve closed {path:?} without responding"));
+    }
+    let response = serde_json::from_str(&response)
+        .wrap_err_with(|| format!("invalid response from {path:?}: {response:?}"))?;
+    Ok(Some(response))
+}
+
+/// Listens on the control socket, taking the place of a server that is no longer running.
+#[tracing::instrument(level = "debug")]
+pub fn listen() -> eyre::Result<UnixListener> {
+    let path = make_runtime_path(SOCKET_FILE_NAME)?;
+
+    // Keeps two servers starting at once from both taking the place of a stale socket
+    let _lock = RuntimeLock::acquire()?;
+    if UnixStream::connect(&path).is_ok() {
+        return Err(eyre!("hyprshade serve is already listening on {path:?}"));
+    }
+    match fs::remove_file(&path) {
+        Ok(()) => debug!("Removed stale socket {path:?}"),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+        Err(err) => return Err(err).wrap_err_with(|| format!("failed to remove {path:?}")),
+    }
+
+    UnixListener::bind(&path).wrap_err_with(|| format!("failed to listen on {path:?}"))
+}
@@ -1,14 +1,93 @@
-use std::{env, path::PathBuf};
+use std::{
+    env,
+    ffi::OsString,
+    path::{Path, PathBuf},
+    sync::{PoisonError, RwLock},
+};
 
-use crate::constants::{HYPRLAND_CONFIG_DIR, HYPRSHADE_CONFIG_DIR, HYPRSHADE_SHADERS_DIR_ENV};
+use crate::constants::{
+    HYPRLAND_CONFIG_DIR, HYPRSHADE_CONFIG_DIR, HYPRSHADE_DATA_DIR, HYPRSHADE_SHADERS_DIR_ENV,
+};
 
+const XDG_DATA_DIRS_ENV: &str = "XDG_DATA_DIRS";
+const XDG_DATA_DIRS_DEFAULT: &str = "/usr/local/share:/usr/share";
+
+static CONFIG_SHADER_DIRS: RwLock<Vec<PathBuf>> = RwLock::new(Vec::new());
+
+/// Directory for shaders written by the user, searched first
+pub fn user_shader_dir() -> PathBuf {
+    HYPRLAND_CONFIG_DIR.to_owned().join("shaders")
+}
+
+/// Sets the `shader_dirs` listed in the config file, replacing those of a config set before.
+pub fn set_config_shader_dirs(dirs: Vec<PathBuf>) {
+    *CONFIG_SHADER_DIRS
+        .write()
+        .unwrap_or_else(PoisonError::into_inner) = dirs;
+}
+
+/// Directories searched for shaders by name, in order of precedence:
+///
+/// 1. `~/.config/hypr/shaders`
+/// 2. `~/.config/hyprshade/shaders`
+/// 3. Each directory in `HYPRSHADE_SHADERS_DIR`
+/// 4. Each directory in `shader_dirs` of the config file
+/// 5. `$XDG_DATA_HOME/hyprshade/shaders`
+/// 6. Each `$XDG_DATA_DIRS/hyprshade/shaders`
 pub fn shader_dirs() -> Vec<PathBuf> {
-    [
-        Some(HYPRLAND_CONFIG_DIR.to_owned().join("shaders")),
-        Some(HYPRSHADE_CONFIG_DIR.to_owned().join("shaders")),
-        env::var(HYPRSHADE_SHADERS_DIR_ENV).map(PathBuf::from).ok(),
-    ]
-    .into_iter()
-    .flatten()
-    .collect()
+    let mut dirs = vec![
+        user_shader_dir(),
+        HYPRSHADE_CONFIG_DIR.to_owned().join("shaders"),
+    ];
+    if let Some(paths) = env::var_os(HYPRSHADE_SHADERS_DIR_ENV) {
+        dirs.extend(split_paths(&paths));
+    }
+    dirs.extend(
+        CONFIG_SHADER_DIRS
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .iter()
+            .cloned(),
+    );
+    dirs.push(HYPRSHADE_DATA_DIR.join("shaders"));
+    dirs.extend(
+        xdg_data_dirs()
+            .into_iter()
+            .map(|dir| dir.join(env!("CARGO_PKG_NAME")).join("shaders")),
+    );
+
+    let mut unique = Vec::with_capacity(dirs.len());
+    for dir in dirs {
+        if !unique.contains(&dir) {
+            unique.push(dir);
+        }
+    }
+    unique
+}
+
+fn xdg_data_dirs() -> Vec<PathBuf> {
+    let paths = env::var_os(XDG_DATA_DIRS_ENV)
+        .filter(|paths| !paths.is_empty())
+        .unwrap_or_else(|| OsString::from(XDG_DATA_DIRS_DEFAULT));
+    split_paths(&paths)
+        // Relative paths are invalid per the XDG base directory spec
+        .filter(|path| path.is_absolute())
+        .collect()
+}
+
+fn split_paths(paths: &OsString) -> impl Iterator<Item = PathBuf> {
+    env::split_paths(paths).filter(|path| path != Path::new(""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split_paths_skips_empty() {
+        assert_eq!(
+            split_paths(&OsString::from("/a::b/c:")).collect::<Vec<_>>(),
+            [PathBuf::from("/a"), PathBuf::from("b/c")]
+        );
+    }
 }
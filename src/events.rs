@@ -0,0 +1,112 @@
+//! Listener for Hyprland's event socket (`.socket2.sock`)
+
+use std::{
+    io::{self, BufRead, BufReader, Lines},
+    os::unix::net::UnixStream,
+    path::{Path, PathBuf},
+};
+
+use color_eyre::Section;
+use eyre::{Context, OptionExt};
+
+use crate::{
+    constants::{HYPRLAND_INSTANCE_SIGNATURE_ENV, hyprland_runtime_dir},
+    instance,
+};
+
+const SOCKET_NAME: &str = ".socket2.sock";
+
+/// Where Hyprland kept its sockets before moving them into the runtime directory
+const LEGACY_SOCKET_DIR: &str = "/tmp/hypr";
+
+/// Separates the name of an event from its data, e.g. `workspace>>2`
+const SEPARATOR: &str = ">>";
+
+#[derive(Debug, PartialEq)]
+pub struct Event {
+    pub name: String,
+    pub data: String,
+}
+
+impl Event {
+    fn parse(line: &str) -> Option<Event> {
+        let (name, data) = line.split_once(SEPARATOR)?;
+        Some(Event {
+            name: name.to_owned(),
+            data: data.to_owned(),
+        })
+    }
+}
+
+/// Events as Hyprland sends them, ending when Hyprland exits
+#[derive(Debug)]
+pub struct Events {
+    lines: Lines<BufReader<UnixStream>>,
+}
+
+impl Iterator for Events {
+    type Item = io::Result<Event>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.lines.next()? {
+                Ok(line) => match Event::parse(&line) {
+                    Some(event) => return Some(Ok(event)),
+                    None => continue,
+                },
+                Err(err) => return Some(Err(err)),
+            }
+        }
+    }
+}
+
+/// Connects to the event socket of the selected Hyprland instance.
+#[tracing::instrument(level = "debug")]
+pub fn listen() -> eyre::Result<Events> {
+    let path = socket_path()?;
+    let stream = UnixStream::connect(&path)
+        .wrap_err_with(|| format!("failed to connect to Hyprland's event socket at {path:?}"))?;
+    Ok(Events {
+        lines: BufReader::new(stream).lines(),
+    })
+}
+
+fn socket_path() -> eyre::Result<PathBuf> {
+    let signature = instance::signature()
+        .ok_or_eyre(format!("{HYPRLAND_INSTANCE_SIGNATURE_ENV} is not set"))
+        .suggestion("Make sure hyprshade is running inside of Hyprland, or pass --instance")?;
+
+    let path = hyprland_runtime_dir()
+        .map(|dir| dir.join(signature).join(SOCKET_NAME))
+        .filter(|path| path.exists())
+        .unwrap_or_else(|| {
+            Path::new(LEGACY_SOCKET_DIR)
+                .join(signature)
+                .join(SOCKET_NAME)
+        });
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        assert_eq!(
+            Event::parse("configreloaded>>"),
+            Some(Event {
+                name: "configreloaded".to_owned(),
+                data: String::new(),
+            })
+        );
+        assert_eq!(
+            Event::parse("activewindow>>kitty,~ >> vim"),
+            Some(Event {
+                name: "activewindow".to_owned(),
+                data: "kitty,~ >> vim".to_owned(),
+            })
+        );
+        assert_eq!(Event::parse("garbage"), None);
+    }
+}
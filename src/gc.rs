@@ -0,0 +1,94 @@
+//! Removes shader instances that are no longer needed from the runtime directory
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+use eyre::Context;
+use tracing::debug;
+
+use crate::{constants::hyprshade_runtime_dir, instance, shader::ShaderInstance};
+
+/// Instances used this recently are kept, since only the shader loaded by Hyprland is known. A
+/// program using the library may have turned them on through another
+/// [`Compositor`](crate::compositor::Compositor).
+pub const RETENTION: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Removes instances that are neither `loaded` by Hyprland nor used within [`RETENTION`],
+/// instances whose rendered file is gone, and temporary files left behind by interrupted writes.
+/// Returns the removed paths, or the paths that would be removed if `dry_run`.
+#[tracing::instrument(level = "debug")]
+pub fn collect(loaded: Option<&Path>, dry_run: bool) -> eyre::Result<Vec<PathBuf>> {
+    let Some(runtime_dir) = hyprshade_runtime_dir().map(instance::namespace) else {
+        return Ok(Vec::new());
+    };
+    let runtime_dir = runtime_dir.as_path();
+    let entries = match fs::read_dir(runtime_dir) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => return Err(err).wrap_err_with(|| format!("failed to read {runtime_dir:?}")),
+    };
+    let now = SystemTime::now();
+    let is_recent = |path: &Path| {
+        fs::metadata(path)
+            .and_then(|m| m.modified())
+            .is_ok_and(|modified| now.duration_since(modified).unwrap_or_default() < RETENTION)
+    };
+
+    let mut garbage = Vec::new();
+    for entry in entries {
+        let path = entry?.path();
+        if !path.is_file() {
+            continue;
+        }
+
+        if is_temporary(&path) {
+            if !is_recent(&path) {
+                garbage.push(path);
+            }
+            continue;
+        }
+
+        // Other files, like `index.json` or the copy in `last.json`, aren't instance sidecars
+        let Ok(instance) = ShaderInstance::read(&path) else {
+            continue;
+        };
+        if instance.sidecar_path() != path {
+            continue;
+        }
+        let instance_path = instance.instance_path();
+        if loaded == Some(instance_path) {
+            continue;
+        }
+        if !instance_path.is_file() {
+            debug!("Rendered file of {path:?} is gone");
+            garbage.push(path);
+        } else if !is_recent(&path) && instance_path.parent() == Some(runtime_dir) {
+            garbage.push(instance_path.to_owned());
+            garbage.push(path);
+        }
+    }
+
+    if !dry_run {
+        for path in &garbage {
+            debug!("Removing {path:?}");
+            match fs::remove_file(path) {
+                Ok(()) => {}
+                Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+                Err(err) => return Err(err).wrap_err_with(|| format!("failed to remove {path:?}")),
+            }
+        }
+    }
+
+    Ok(garbage)
+}
+
+/// Whether `path` was written by [`write_atomic`](crate::util::write_atomic) before being renamed
+/// into place.
+fn is_temporary(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| name.starts_with('.') && name.ends_with(".tmp"))
+}
@@ -5,9 +5,10 @@ use std::{
     process::{Command, Output, Stdio},
 };
 
-use color_eyre::{Section, SectionExt};
 use serde::{Deserialize, Serialize, de::DeserializeOwned};
 
+use crate::instance;
+
 pub const PROGRAM_NAME: &str = "hyprctl";
 
 /// Special value for `decoration:screen_shader` meaning no shader is applied
@@ -15,41 +16,55 @@ const SHADER_EMPTY_STRING: &str = "[[EMPTY]]";
 
 pub mod shader {
     use std::{
+        cell::RefCell,
         ffi::OsStr,
         path::{Path, PathBuf},
     };
 
-    use super::{HyprctlOption, JsonExt, OutputExt, SHADER_EMPTY_STRING, hyprctl_command};
-    use color_eyre::eyre::WrapErr;
+    use super::{Error, HyprctlOption, JsonExt, OutputExt, SHADER_EMPTY_STRING, hyprctl_command};
 
     const VARIABLE_NAME: &str = "decoration:screen_shader";
 
+    thread_local! {
+        /// Screen shader this thread set last, with [`None`] inside if it was cleared
+        static SET: RefCell<Option<Option<PathBuf>>> = const { RefCell::new(None) };
+    }
+
+    /// Returns the screen shader this thread set since the last call, which is known without
+    /// asking Hyprland again, or [`None`] if it wasn't set.
+    pub fn take_set() -> Option<Option<PathBuf>> {
+        SET.take()
+    }
+
     #[tracing::instrument(level = "debug")]
-    pub fn get() -> eyre::Result<Option<PathBuf>> {
+    pub fn get() -> Result<Option<PathBuf>, Error> {
         let option = hyprctl_command()
             .args(["-j", "getoption", VARIABLE_NAME])
-            .json::<HyprctlOption>()
-            .wrap_err("failed to find current screen shader")?;
+            .json::<HyprctlOption>()?;
 
         Ok(option.into_value().map(PathBuf::from))
     }
 
     #[tracing::instrument(level = "debug")]
-    pub fn set(shader_path: &Path) -> eyre::Result<()> {
+    pub fn set(shader_path: &Path) -> Result<(), Error> {
         debug_assert!(
             shader_path.is_absolute(),
             "path should be canonicalized before passing to hyprctl::shader::set"
         );
-        _set(shader_path.as_os_str()).wrap_err("failed to set screen shader")
+        _set(shader_path.as_os_str())?;
+        SET.set(Some(Some(shader_path.to_owned())));
+        Ok(())
     }
 
     #[tracing::instrument(level = "debug")]
-    pub fn clear() -> eyre::Result<()> {
-        _set(SHADER_EMPTY_STRING.as_ref()).wrap_err("failed to turn off screen shader")
+    pub fn clear() -> Result<(), Error> {
+        _set(SHADER_EMPTY_STRING.as_ref())?;
+        SET.set(Some(None));
+        Ok(())
     }
 
     #[tracing::instrument(level = "debug")]
-    fn _set(shader_path: &OsStr) -> eyre::Result<()> {
+    fn _set(shader_path: &OsStr) -> Result<(), Error> {
         hyprctl_command()
             .args(["keyword", VARIABLE_NAME])
             .arg(shader_path)
@@ -61,6 +76,9 @@ pub mod shader {
 
 fn hyprctl_command() -> Command {
     let mut command = Command::new(PROGRAM_NAME);
+    if let Some(signature) = instance::signature() {
+        command.args(["-i", signature]);
+    }
     command.stdin(Stdio::null());
     command
 }
@@ -87,73 +105,45 @@ impl HyprctlOption {
 }
 
 trait OutputExt {
-    fn output_with_check(&mut self) -> eyre::Result<Output>;
+    fn output_with_check(&mut self) -> Result<Output, Error>;
 }
 
 impl OutputExt for Command {
-    fn output_with_check(&mut self) -> eyre::Result<Output> {
+    fn output_with_check(&mut self) -> Result<Output, Error> {
         let output = self.output().map_err(|err| {
             if err.kind() == io::ErrorKind::NotFound {
-                eyre::Report::from(Error::Io(err))
-                    .with_suggestion(|| format!("Is {PROGRAM_NAME} located in your PATH?"))
+                Error::NotFound(err)
             } else {
-                Error::Io(err).into()
+                Error::Io(err)
             }
         })?;
 
         if output.status.success() {
             Ok(output)
         } else if let Some(signal) = output.status.signal() {
-            Err(Error::Signal(signal).into())
+            Err(Error::Signal(signal))
+        } else if let Some(code) = output.status.code() {
+            Err(Error::ExitCode {
+                code,
+                stderr: String::from_utf8_lossy(&output.stderr).trim().to_owned(),
+            })
         } else {
-            let err: eyre::Report = if let Some(code) = output.status.code() {
-                Error::ExitCode(code).into()
-            } else {
-                Error::Unknown.into()
-            };
-            Err(err).command_sections(self, &output)
+            Err(Error::Unknown)
         }
     }
 }
 
 trait JsonExt {
-    fn json<T: DeserializeOwned>(&mut self) -> eyre::Result<T>;
+    fn json<T: DeserializeOwned>(&mut self) -> Result<T, Error>;
 }
 
 impl JsonExt for Command {
-    fn json<T: DeserializeOwned>(&mut self) -> eyre::Result<T> {
+    fn json<T: DeserializeOwned>(&mut self) -> Result<T, Error> {
         let output = self.output_with_check()?;
-        let value = serde_json::from_slice(&output.stdout)
-            .map_err(Error::SerdeJson)
-            .command_sections(self, &output)
-            .suggestion("This is likely a bug in Hyprland. Go bug Vaxry about it (nicely :))")?;
-
-        Ok(value)
-    }
-}
-
-trait CommandSectionExt: Section {
-    fn command_sections(self, command: &Command, output: &Output) -> Self::Return;
-}
-
-impl<T, E> CommandSectionExt for eyre::Result<T, E>
-where
-    E: Into<eyre::Report>,
-{
-    fn command_sections(self, command: &Command, output: &Output) -> Self::Return {
-        self.with_section(|| format!("{:?}", command).header("Command:"))
-            .with_section(|| {
-                String::from_utf8_lossy(&output.stdout)
-                    .trim()
-                    .to_string()
-                    .header("Stdout:")
-            })
-            .with_section(|| {
-                String::from_utf8_lossy(&output.stderr)
-                    .trim()
-                    .to_string()
-                    .header("Stderr:")
-            })
+        serde_json::from_slice(&output.stdout).map_err(|source| Error::SerdeJson {
+            stdout: String::from_utf8_lossy(&output.stdout).trim().to_owned(),
+            source,
+        })
     }
 }
 
@@ -161,13 +151,21 @@ where
 #[derive(thiserror::Error, Debug)]
 pub enum Error {
     #[error("failed to execute {PROGRAM_NAME}")]
-    Io(#[from] io::Error),
-    #[error("failed to parse JSON returned by {PROGRAM_NAME}")]
-    SerdeJson(#[from] serde_json::Error),
+    Io(#[source] io::Error),
+    #[error("failed to execute {PROGRAM_NAME} (is it located in your PATH?)")]
+    NotFound(#[source] io::Error),
+    #[error("failed to parse JSON returned by {PROGRAM_NAME}: {stdout:?}")]
+    SerdeJson {
+        stdout: String,
+        source: serde_json::Error,
+    },
     #[error("{PROGRAM_NAME} terminated by signal {0}")]
     Signal(i32),
-    #[error("{PROGRAM_NAME} terminated with exit code {0}")]
-    ExitCode(i32),
+    #[error(
+        "{PROGRAM_NAME} terminated with exit code {code}{}",
+        if stderr.is_empty() { String::new() } else { format!(": {stderr}") }
+    )]
+    ExitCode { code: i32, stderr: String },
     #[error("{PROGRAM_NAME} terminated unsuccessfully (unknown cause)")]
     Unknown,
 }
@@ -200,6 +198,6 @@ mod tests {
             .args(["{"])
             .json::<serde_json::Value>()
             .unwrap_err();
-        assert!(err.downcast_ref::<Error>().is_some());
+        assert!(matches!(err, Error::SerdeJson { .. }), "{err:?}");
     }
 }
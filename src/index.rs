@@ -0,0 +1,243 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    sync::{
+        Arc, Mutex, PoisonError,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::{Duration, SystemTime},
+};
+
+use serde::{Deserialize, Serialize};
+use tracing::{debug, trace};
+use walkdir::WalkDir;
+
+use crate::{
+    constants::hyprshade_runtime_dir,
+    dirs::shader_dirs,
+    resolver::{MAX_DEPTH, is_resolvable},
+    util::write_atomic,
+};
+
+const INDEX_FILE_NAME: &str = "index.json";
+
+/// Modifications this close to when a directory was indexed may share its mtime, so such
+/// directories are walked again rather than trusted (as git does for "racily clean" entries).
+const RACY_INTERVAL: Duration = Duration::from_secs(2);
+
+static NO_CACHE: AtomicBool = AtomicBool::new(false);
+static INDEX: Mutex<Option<Arc<ShaderIndex>>> = Mutex::new(None);
+
+/// Files in each shader directory, persisted in the runtime directory so that resolving a name
+/// doesn't require walking every shader directory
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct ShaderIndex {
+    dirs: Vec<DirIndex>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct DirIndex {
+    root: PathBuf,
+    indexed_at: SystemTime,
+    /// Modification time of every directory walked, including `root` (which may not exist)
+    mtimes: Vec<(PathBuf, Option<SystemTime>)>,
+    files: Vec<PathBuf>,
+}
+
+impl ShaderIndex {
+    /// Makes [`ShaderIndex::get`] walk shader directories instead of reading a cached index.
+    pub fn disable_cache() {
+        NO_CACHE.store(true, Ordering::Relaxed);
+    }
+
+    /// Returns the index for the current shader directories, rebuilding any that changed.
+    ///
+    /// The index is kept for the rest of the process; see [`ShaderIndex::refresh`].
+    pub fn get() -> Arc<ShaderIndex> {
+        let mut index = INDEX.lock().unwrap_or_else(PoisonError::into_inner);
+        index
+            .get_or_insert_with(|| Arc::new(ShaderIndex::load(None)))
+            .clone()
+    }
+
+    /// Rebuilds directories that changed since the index was returned by [`ShaderIndex::get`], for
+    /// processes that resolve shaders again later on.
+    pub fn refresh() {
+        let mut index = INDEX.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some(previous) = index.take() {
+            let previous = Arc::unwrap_or_clone(previous);
+            *index = Some(Arc::new(ShaderIndex::load(Some(previous))));
+        }
+    }
+
+    /// Reuses `previous` if it is fresh, or else the index persisted in the runtime directory.
+    fn load(previous: Option<ShaderIndex>) -> ShaderIndex {
+        let dirs = shader_dirs();
+        if NO_CACHE.load(Ordering::Relaxed) {
+            debug!("Shader index cache disabled");
+            return ShaderIndex::build(&dirs, previous.unwrap_or_default());
+        }
+
+        let path = index_path();
+        let cached = match (previous, &path) {
+            (Some(previous), _) => previous,
+            (None, Some(path)) => ShaderIndex::read(path).unwrap_or_else(|err| {
+                debug!("Ignoring shader index {path:?}: {err}");
+                ShaderIndex::default()
+            }),
+            (None, None) => ShaderIndex::default(),
+        };
+
+        let is_fresh = cached.dirs.len() == dirs.len()
+            && cached
+                .dirs
+                .iter()
+                .zip(&dirs)
+                .all(|(d, dir)| d.is_fresh(dir));
+        if is_fresh {
+            trace!("Using cached shader index");
+            return cached;
+        }
+
+        let index = ShaderIndex::build(&dirs, cached);
+        match &path {
+            Some(path) => {
+                if let Err(err) = index.write(path) {
+                    debug!("Failed to write shader index {path:?}: {err}");
+                }
+            }
+            None => debug!("No runtime directory for shader index"),
+        }
+        index
+    }
+
+    /// Files in `dir` that may be resolved by name, or [`None`] if `dir` isn't a shader directory.
+    pub fn files_in(&self, dir: &Path) -> Option<&[PathBuf]> {
+        self.dirs
+            .iter()
+            .find(|d| d.root == dir)
+            .map(|d| d.files.as_slice())
+    }
+
+    /// Reuses entries of `previous` that are still fresh.
+    fn build(dirs: &[PathBuf], mut previous: ShaderIndex) -> ShaderIndex {
+        let dirs = dirs
+            .iter()
+            .map(
+                |dir| match previous.dirs.iter().position(|d| d.is_fresh(dir)) {
+                    Some(i) => previous.dirs.swap_remove(i),
+                    None => DirIndex::build(dir),
+                },
+            )
+            .collect();
+        ShaderIndex { dirs }
+    }
+
+    fn read(path: &Path) -> io::Result<ShaderIndex> {
+        let contents = fs::read(path)?;
+        serde_json::from_slice(&contents).map_err(io::Error::other)
+    }
+
+    fn write(&self, path: &Path) -> io::Result<()> {
+        let contents = serde_json::to_vec(self).map_err(io::Error::other)?;
+        write_atomic(path, contents)
+    }
+}
+
+impl DirIndex {
+    #[tracing::instrument(level = "debug")]
+    fn build(root: &Path) -> DirIndex {
+        let indexed_at = SystemTime::now();
+        let mut mtimes = vec![(root.to_owned(), mtime(root))];
+        let mut files = Vec::new();
+
+        for entry in WalkDir::new(root)
+            .min_depth(1)
+            .max_depth(MAX_DEPTH)
+            .into_iter()
+            .filter_map(|e| {
+                e.inspect_err(|err| {
+                    debug!("Ignoring error encountered when walking directory {root:?}");
+                    debug!(?err);
+                })
+                .ok()
+            })
+        {
+            if entry.file_type().is_dir() {
+                mtimes.push((entry.path().to_owned(), mtime(entry.path())));
+            } else if entry.file_type().is_file() && is_resolvable(entry.path()) {
+                files.push(entry.into_path());
+            }
+        }
+
+        DirIndex {
+            root: root.to_owned(),
+            indexed_at,
+            mtimes,
+            files,
+        }
+    }
+
+    fn is_fresh(&self, root: &Path) -> bool {
+        self.root == root
+            && self.mtimes.iter().all(|(path, indexed_mtime)| {
+                let is_racy = indexed_mtime
+                    .is_some_and(|indexed_mtime| indexed_mtime + RACY_INTERVAL > self.indexed_at);
+                !is_racy && mtime(path) == *indexed_mtime
+            })
+    }
+}
+
+fn mtime(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+fn index_path() -> Option<PathBuf> {
+    let runtime_dir = hyprshade_runtime_dir()?;
+    fs::create_dir_all(runtime_dir).ok()?;
+    Some(runtime_dir.join(INDEX_FILE_NAME))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_changes() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let root = tempdir.path().join("shaders");
+        fs::create_dir_all(root.join("sub")).unwrap();
+        fs::write(root.join("sub/a.glsl"), "").unwrap();
+        fs::write(root.join("a.toml"), "").unwrap();
+
+        let mut index = DirIndex::build(&root);
+        assert_eq!(index.files, [root.join("sub/a.glsl")]);
+
+        // Pretend the index was built long after the last modification
+        index.indexed_at += RACY_INTERVAL * 2;
+        assert!(index.is_fresh(&root));
+        assert!(!index.is_fresh(tempdir.path()));
+
+        fs::write(root.join("sub/b.glsl"), "").unwrap();
+        assert!(!index.is_fresh(&root));
+    }
+
+    #[test]
+    fn racy_entries_are_not_fresh() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let index = DirIndex::build(tempdir.path());
+        assert!(!index.is_fresh(tempdir.path()));
+    }
+
+    #[test]
+    fn missing_root_is_indexed() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let root = tempdir.path().join("missing");
+        let index = DirIndex::build(&root);
+        assert!(index.files.is_empty());
+        assert!(index.is_fresh(&root));
+
+        fs::create_dir(&root).unwrap();
+        assert!(!index.is_fresh(&root));
+    }
+}
@@ -0,0 +1,85 @@
+//! Pausing the schedule, so that `auto` leaves the screen alone for a while
+
+use std::{fs, io};
+
+use chrono::{DateTime, Local};
+use eyre::Context;
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+use crate::{
+    timer::Timer,
+    util::{make_runtime_path, write_atomic},
+};
+
+const FILE_NAME: &str = "inhibit.json";
+
+/// Resumes the schedule when an inhibit expires
+static TIMER: Timer = Timer::new("inhibit");
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Inhibit {
+    /// Inhibits until [`end`](Inhibit::end) if [`None`]
+    until: Option<DateTime<Local>>,
+}
+
+impl Inhibit {
+    pub fn new(until: Option<DateTime<Local>>) -> Self {
+        Self { until }
+    }
+
+    pub fn until(&self) -> Option<DateTime<Local>> {
+        self.until
+    }
+
+    /// Returns the inhibit in effect at the time `now` returns, forgetting it if it has expired.
+    /// `now` is only called for an inhibit that expires.
+    pub fn active(now: impl FnOnce() -> DateTime<Local>) -> eyre::Result<Option<Inhibit>> {
+        let path = make_runtime_path(FILE_NAME)?;
+        let contents = match fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err).wrap_err_with(|| format!("failed to read {path:?}")),
+        };
+        let inhibit: Inhibit = serde_json::from_str(&contents)
+            .wrap_err_with(|| format!("failed to parse {path:?}"))?;
+
+        if inhibit.until.is_some_and(|until| until <= now()) {
+            debug!("Inhibit expired at {:?}", inhibit.until);
+            Inhibit::remove()?;
+            return Ok(None);
+        }
+        Ok(Some(inhibit))
+    }
+
+    /// Saves the inhibit, replacing any other, and arranges for the schedule to resume when it
+    /// expires.
+    pub fn start(&self) -> eyre::Result<()> {
+        let path = make_runtime_path(FILE_NAME)?;
+        write_atomic(&path, serde_json::to_string(self)?)
+            .wrap_err_with(|| format!("failed to write {path:?}"))?;
+
+        match self.until {
+            Some(until) => TIMER
+                .schedule(until, &["auto"])
+                .wrap_err("failed to schedule resuming the schedule")?,
+            None => TIMER.cancel(),
+        }
+        Ok(())
+    }
+
+    /// Ends the inhibit in effect, returning whether there was one.
+    pub fn end() -> eyre::Result<bool> {
+        TIMER.cancel();
+        Inhibit::remove()
+    }
+
+    fn remove() -> eyre::Result<bool> {
+        let path = make_runtime_path(FILE_NAME)?;
+        match fs::remove_file(&path) {
+            Ok(()) => Ok(true),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(false),
+            Err(err) => Err(err).wrap_err_with(|| format!("failed to remove {path:?}")),
+        }
+    }
+}
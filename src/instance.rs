@@ -0,0 +1,101 @@
+//! Hyprland instance whose shader is changed, for when more than one is running (e.g. nested
+//! sessions)
+//!
+//! State of each instance is kept apart in a directory named after its signature within the
+//! runtime directory, so that their rendered shaders and schedules don't collide.
+
+use std::{
+    env, fs, io,
+    os::unix::net::UnixStream,
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
+
+use crate::constants::{HYPRLAND_INSTANCE_SIGNATURE_ENV, hyprland_runtime_dir};
+
+/// Hyprland's request socket, present in the runtime directory of every instance
+const SOCKET_NAME: &str = ".socket.sock";
+
+static SIGNATURE: OnceLock<Option<String>> = OnceLock::new();
+
+/// Selects the instance with `signature` instead of the one hyprshade is running in. Has no
+/// effect once the instance was looked up.
+pub fn select(signature: String) {
+    _ = SIGNATURE.set(Some(signature));
+}
+
+/// Signature of the selected instance, or else of the instance hyprshade is running in.
+pub fn signature() -> Option<&'static str> {
+    SIGNATURE
+        .get_or_init(|| {
+            env::var(HYPRLAND_INSTANCE_SIGNATURE_ENV)
+                .ok()
+                .filter(|signature| is_signature(signature))
+        })
+        .as_deref()
+}
+
+/// Directory within `runtime_dir` for the state of the selected instance, which is `runtime_dir`
+/// itself if there is no instance.
+pub fn namespace(runtime_dir: &Path) -> PathBuf {
+    match signature() {
+        Some(signature) => runtime_dir.join(signature),
+        None => runtime_dir.to_owned(),
+    }
+}
+
+/// Signatures of the running instances, in order.
+#[tracing::instrument(level = "debug")]
+pub fn running() -> io::Result<Vec<String>> {
+    let Some(hyprland_runtime_dir) = hyprland_runtime_dir() else {
+        return Ok(Vec::new());
+    };
+    let entries = match fs::read_dir(hyprland_runtime_dir) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => return Err(err),
+    };
+
+    let mut signatures = Vec::new();
+    for entry in entries {
+        let entry = entry?;
+        let Ok(signature) = entry.file_name().into_string() else {
+            continue;
+        };
+        // Instances that crashed leave their directory behind
+        if is_signature(&signature) && UnixStream::connect(entry.path().join(SOCKET_NAME)).is_ok() {
+            signatures.push(signature);
+        }
+    }
+    signatures.sort();
+    Ok(signatures)
+}
+
+/// Parses `--instance`, which names a directory and so mustn't be a path.
+pub fn parse_signature(value: &str) -> Result<String, String> {
+    if is_signature(value) {
+        Ok(value.to_owned())
+    } else {
+        Err(format!("{value:?} is not an instance signature"))
+    }
+}
+
+fn is_signature(value: &str) -> bool {
+    !value.is_empty() && value != "." && value != ".." && !value.contains('/')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        assert_eq!(
+            parse_signature("a3e7_1720000000_123").as_deref(),
+            Ok("a3e7_1720000000_123")
+        );
+        assert!(parse_signature("").is_err());
+        assert!(parse_signature("..").is_err());
+        assert!(parse_signature("../hypr").is_err());
+    }
+}
@@ -1,19 +1,86 @@
-//! **WARNING:** This library crate is not meant to be used directly.
-//! Breaking changes may occur in any of the exported items without warning.
-
-#![cfg(feature = "_lib")]
-#![allow(dead_code)]
+//! Library behind the `hyprshade` command line tool, for programs that want to find, schedule,
+//! render, and turn on screen shaders themselves.
+//!
+//! ```no_run
+//! use hyprshade::{Config, Hyprland, Resolver, Schedule, Shader, TemplateDataMap};
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! // Turn on a shader by name, as `hyprshade on vibrance` would
+//! let shader = Resolver::with_name("vibrance").resolve()?;
+//! shader.on(&Hyprland, &TemplateDataMap::new())?;
+//!
+//! // Turn on whatever the schedule says, as `hyprshade auto` would
+//! if let Some(config) = Config::find()? {
+//!     config.add_shader_dirs();
+//!     match Schedule::with_config(&config).scheduled_shader(&chrono::Local::now())? {
+//!         Some(shader) => {
+//!             let data = config.data(shader.name()).cloned().unwrap_or_default();
+//!             shader.on(&Hyprland, &data)?;
+//!         }
+//!         None => Shader::off(&Hyprland)?,
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Shaders are applied through a [`Compositor`], which is [`Hyprland`] for the command line tool.
+//! Rendered shaders are kept in `$XDG_RUNTIME_DIR/hyprshade`, in a directory per Hyprland instance
+//! signature, shared with the command line tool. Turning shaders on and off waits for hyprshade
+//! processes that are doing the same. Only what the command line tool turns on is remembered for
+//! `hyprshade restore`.
 
 mod builtin;
+mod compositor;
+mod config;
+// Shared with the command line tool, which uses more of them
+#[allow(dead_code)]
 mod constants;
 mod dirs;
+#[allow(dead_code)]
 mod hyprctl;
+#[allow(dead_code)]
+mod index;
+#[allow(dead_code)]
+mod instance;
+mod lock;
+#[allow(dead_code)]
+mod metadata;
 mod resolver;
+mod schedule;
 mod shader;
 mod template;
+#[allow(dead_code)]
 mod util;
 
-#[doc(hidden)]
-pub mod __private {
-    pub use crate::shader::{Shader, ShaderInstance};
+pub use crate::{
+    builtin::{BuiltinShader, Variable},
+    compositor::{Compositor, Hyprland},
+    config::{Config, Shader as ShaderConfig},
+    resolver::{Candidate, Resolver},
+    schedule::Schedule,
+    shader::{Rendered, Shader, ShaderInstance},
+    template::{TemplateData, TemplateDataMap},
+};
+
+/// Errors returned by the library
+pub mod error {
+    pub use crate::{
+        builtin::{
+            BakeError, RenderError as BuiltinRenderError,
+            lut::{
+                Error as LutError, ParseError as LutParseError, ParseErrorKind as LutParseErrorKind,
+            },
+        },
+        config::ConfigReadError,
+        hyprctl::Error as HyprctlError,
+        lock::Error as LockError,
+        metadata::Error as MetadataError,
+        resolver::{Error as ResolveError, ErrorFromName, ErrorFromPath},
+        shader::{
+            Error as ShaderError, ReadShaderInstanceError, ShaderConversionError,
+            WriteShaderInstanceError,
+        },
+        template::TemplateDataCliParseError,
+    };
 }
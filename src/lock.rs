@@ -0,0 +1,74 @@
+//! Keeps hyprshade processes from interleaving, e.g. when a keybind is pressed twice in quick
+//! succession or a timer fires during `toggle`
+
+use std::{
+    cell::RefCell,
+    fs::File,
+    io,
+    path::PathBuf,
+    sync::{Arc, Weak},
+};
+
+use rustix::fs::{FlockOperation, flock};
+use tracing::debug;
+
+use crate::util::make_runtime_path;
+
+const FILE_NAME: &str = "lock";
+
+thread_local! {
+    /// Lock held by this thread, which acquiring again shares instead of waiting for itself
+    static HELD: RefCell<Weak<File>> = const { RefCell::new(Weak::new()) };
+}
+
+/// Exclusive lock on the runtime directory, released when dropped
+#[derive(Debug)]
+pub struct RuntimeLock {
+    _file: Arc<File>,
+}
+
+impl RuntimeLock {
+    /// Blocks until no other process holds the lock. A thread that already holds it gets it
+    /// again right away, so that e.g. [`Shader::on`](crate::shader::Shader::on) can lock on its
+    /// own within a subcommand that holds the lock.
+    #[tracing::instrument(level = "debug")]
+    pub fn acquire() -> Result<RuntimeLock, Error> {
+        if let Some(file) = HELD.with_borrow(Weak::upgrade) {
+            return Ok(RuntimeLock { _file: file });
+        }
+
+        let path = make_runtime_path(FILE_NAME).map_err(Error::RuntimeDir)?;
+        let file = File::options()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&path)
+            .map_err(|source| Error::Open {
+                path: path.clone(),
+                source,
+            })?;
+
+        if flock(&file, FlockOperation::NonBlockingLockExclusive).is_err() {
+            debug!("Waiting for another hyprshade process to finish");
+            flock(&file, FlockOperation::LockExclusive).map_err(|errno| Error::Lock {
+                path,
+                source: errno.into(),
+            })?;
+        }
+
+        let file = Arc::new(file);
+        HELD.set(Arc::downgrade(&file));
+        Ok(RuntimeLock { _file: file })
+    }
+}
+
+#[non_exhaustive]
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("failed to create runtime directory")]
+    RuntimeDir(#[source] io::Error),
+    #[error("failed to open {path:?}")]
+    Open { path: PathBuf, source: io::Error },
+    #[error("failed to lock {path:?}")]
+    Lock { path: PathBuf, source: io::Error },
+}
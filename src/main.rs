@@ -1,23 +1,45 @@
 mod builtin;
 mod cli;
+mod compositor;
 mod config;
 mod constants;
+mod control;
 mod dirs;
+mod events;
+mod gc;
 mod hyprctl;
+mod index;
+mod inhibit;
+mod instance;
+mod lock;
+mod metadata;
+mod pack;
 mod resolver;
 mod schedule;
+// Shared with the library, which also turns shaders on without remembering them
+#[allow(dead_code)]
 mod shader;
 mod template;
 mod time;
+mod timed;
+mod timer;
 mod util;
 
 use std::process::ExitCode;
 
-use clap::Parser;
+use clap::{CommandFactory, Parser};
+use clap_complete::CompleteEnv;
 
-use crate::cli::{Cli, CommandExecute};
+use crate::{
+    cli::{Cli, CommandExecute},
+    constants::COMPLETE_ENV,
+};
 
 fn main() -> eyre::Result<ExitCode> {
+    CompleteEnv::with_factory(Cli::command)
+        .var(COMPLETE_ENV)
+        .complete();
+
     color_eyre::config::HookBuilder::default()
         .display_env_section(cfg!(debug_assertions))
         .install()?;
@@ -25,6 +47,11 @@ fn main() -> eyre::Result<ExitCode> {
     let cli = Cli::parse();
 
     cli.instrumentation.setup()?;
+    cli.select_instance();
+
+    if let Some(exit_code) = cli.forward()? {
+        return Ok(exit_code);
+    }
 
     let config = cli.config()?;
 
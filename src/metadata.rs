@@ -0,0 +1,176 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    template::{TemplateData, TemplateDataMap},
+    util::PathExt,
+};
+
+/// Extension of metadata sidecars, which are never resolved as shaders
+pub const SIDECAR_EXTENSION: &str = "toml";
+
+/// Returns the path of the metadata sidecar for the shader at `shader_path`, e.g.
+/// `my-vibrance.toml` for `my-vibrance.glsl.mustache`.
+pub fn sidecar_path(shader_path: &Path) -> Option<PathBuf> {
+    let prefix = PathExt::file_prefix(shader_path)?;
+    let mut file_name = prefix.to_owned();
+    file_name.push(".");
+    file_name.push(SIDECAR_EXTENSION);
+    Some(shader_path.with_file_name(file_name))
+}
+
+/// Reads the metadata sidecar of `shader_path`, or an empty table if it has none.
+pub fn read(shader_path: &Path) -> Result<toml::Table, Error> {
+    let Some(path) = sidecar_path(shader_path) else {
+        return Ok(toml::Table::new());
+    };
+    let contents = match fs::read_to_string(&path) {
+        Ok(contents) => contents,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(toml::Table::new()),
+        Err(source) => return Err(Error::Io { path, source }),
+    };
+    toml::from_str(&contents).map_err(|source| Error::Parse { path, source })
+}
+
+/// Reads the defaults of the variables listed in the metadata sidecar of `shader_path`, if it has
+/// one.
+pub fn read_defaults(shader_path: &Path) -> Result<TemplateDataMap, Error> {
+    Ok(match read(shader_path)?.get("variables") {
+        Some(toml::Value::Table(variables)) => defaults(variables),
+        _ => TemplateDataMap::new(),
+    })
+}
+
+/// Variables are tables with a `type`, or tables of variables.
+pub fn defaults(variables: &toml::Table) -> TemplateDataMap {
+    variables
+        .iter()
+        .filter_map(|(key, value)| Some((key.to_owned(), default_value(value.as_table()?)?)))
+        .collect()
+}
+
+/// Returns the default of `variable`, or the defaults of the variables in it if it is a table of
+/// variables.
+pub fn default_value(variable: &toml::Table) -> Option<TemplateData> {
+    if !variable.contains_key("type") {
+        return Some(TemplateData::from(defaults(variable)));
+    }
+    match variable.get("default")? {
+        toml::Value::Float(value) => Some(TemplateData::Float(*value)),
+        toml::Value::Integer(value) => Some(TemplateData::Float(*value as f64)),
+        toml::Value::Boolean(value) => Some(TemplateData::Bool(*value)),
+        toml::Value::String(value) => Some(TemplateData::Enum(value.to_owned())),
+        _ => None,
+    }
+}
+
+/// Returns the variable at the nested key `path` (e.g. `["balance", "red"]`) of `metadata`.
+pub fn variable<'a, S: AsRef<str>>(
+    metadata: &'a toml::Table,
+    path: &[S],
+) -> Option<&'a toml::Table> {
+    path.iter()
+        .try_fold(metadata.get("variables")?.as_table()?, |variables, key| {
+            variables.get(key.as_ref())?.as_table()
+        })
+}
+
+/// Reads a number, which may be written as an integer.
+pub fn number(value: &toml::Value) -> Option<f64> {
+    match value {
+        toml::Value::Float(value) => Some(*value),
+        toml::Value::Integer(value) => Some(*value as f64),
+        _ => None,
+    }
+}
+
+#[non_exhaustive]
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("could not read shader metadata {path:?}")]
+    Io { path: PathBuf, source: io::Error },
+    #[error("could not parse shader metadata {path:?}")]
+    Parse {
+        path: PathBuf,
+        source: toml::de::Error,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sidecar_paths() {
+        assert_eq!(
+            sidecar_path(Path::new("/a/my-vibrance.glsl.mustache")),
+            Some(PathBuf::from("/a/my-vibrance.toml"))
+        );
+        assert_eq!(
+            sidecar_path(Path::new("warm.glsl")),
+            Some(PathBuf::from("warm.toml"))
+        );
+    }
+
+    #[test]
+    fn defaults_from_variables() {
+        let variables: toml::Table = toml::from_str(
+            r#"
+                [strength]
+                type = "float"
+                default = 0.5
+
+                [type]
+                type = "enum"
+                variants = ["a", "b"]
+                default = "b"
+
+                [lut]
+                type = "path"
+
+                [balance.red]
+                type = "float"
+                default = 1
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            defaults(&variables),
+            TemplateDataMap::from([
+                ("strength".into(), TemplateData::Float(0.5)),
+                ("type".into(), TemplateData::Enum("b".into())),
+                (
+                    "balance".into(),
+                    TemplateData::from([("red".into(), TemplateData::Float(1.0))])
+                ),
+            ])
+        );
+    }
+
+    #[test]
+    fn variable_lookup() {
+        let metadata: toml::Table = toml::from_str(
+            r#"
+                [variables.strength]
+                type = "float"
+
+                [variables.balance.red]
+                type = "float"
+            "#,
+        )
+        .unwrap();
+
+        let type_of = |path: &[&str]| {
+            let variable = variable(&metadata, path)?;
+            Some(variable.get("type").map(|t| t.as_str().unwrap().to_owned()))
+        };
+        assert_eq!(type_of(&["strength"]), Some(Some("float".into())));
+        assert_eq!(type_of(&["balance"]), Some(None));
+        assert_eq!(type_of(&["balance", "red"]), Some(Some("float".into())));
+        assert_eq!(type_of(&["strength", "red"]), None);
+        assert_eq!(type_of(&["invalid"]), None);
+    }
+}
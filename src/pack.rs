@@ -0,0 +1,581 @@
+use std::{
+    collections::HashMap,
+    ffi::{OsStr, OsString},
+    fmt, fs,
+    io::{self, BufReader, Read, Seek},
+    os::unix::ffi::OsStrExt,
+    path::{Component, Path, PathBuf},
+    process,
+};
+
+use flate2::read::GzDecoder;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, trace};
+
+use crate::{
+    builtin::BuiltinShader,
+    constants::{HYPRSHADE_CONFIG_DIR, PACK_MANIFEST_FILE_NAME},
+    dirs::shader_dirs,
+    metadata,
+    resolver::{shader_files, walk_shader_files},
+    shader::TEMPLATE_EXTENSION,
+    util::PathExt,
+};
+
+const SHADER_EXTENSIONS: &[&str] = &["glsl", "frag"];
+const TARBALL_SUFFIXES: &[&str] = &[".tar.gz", ".tgz", ".tar"];
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// A collection of shaders installed into its own directory under [`Pack::packs_dir`]
+#[derive(Debug, Clone)]
+pub struct Pack {
+    manifest: Manifest,
+    path: PathBuf,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Manifest {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    /// Shader files relative to the root of the pack. If empty, all shader files are included.
+    #[serde(default)]
+    pub shaders: Vec<PathBuf>,
+    #[serde(default, skip_serializing_if = "toml::Table::is_empty")]
+    pub metadata: toml::Table,
+}
+
+/// Another shader that resolves to the same name as a shader in a pack
+#[derive(Debug, Clone, PartialEq)]
+pub enum Collision {
+    Builtin { name: String },
+    File { name: String, path: PathBuf },
+}
+
+impl Pack {
+    pub fn packs_dir() -> PathBuf {
+        HYPRSHADE_CONFIG_DIR.join("shaders")
+    }
+
+    /// Returns all installed packs, sorted by name.
+    pub fn installed() -> Result<Vec<Pack>, Error> {
+        let packs_dir = Self::packs_dir();
+        let entries = match fs::read_dir(&packs_dir) {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(source) => {
+                return Err(Error::Io {
+                    path: packs_dir,
+                    source,
+                });
+            }
+        };
+
+        let mut packs = entries
+            .filter_map(|entry| {
+                entry
+                    .inspect_err(|err| debug!("Ignoring error reading {packs_dir:?}: {err}"))
+                    .ok()
+            })
+            .filter(|entry| entry.path().join(PACK_MANIFEST_FILE_NAME).is_file())
+            .map(|entry| Pack::read(entry.path()))
+            .collect::<Result<Vec<_>, _>>()?;
+        packs.sort_by(|a, b| a.name().cmp(b.name()));
+
+        Ok(packs)
+    }
+
+    /// Returns the installed pack named `name`, if there is one.
+    pub fn get(name: &str) -> Result<Option<Pack>, Error> {
+        validate_name(name)?;
+        let path = Self::packs_dir().join(name);
+        if path.join(PACK_MANIFEST_FILE_NAME).is_file() {
+            Pack::read(path).map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn read(path: PathBuf) -> Result<Pack, Error> {
+        let manifest = Manifest::read(&path.join(PACK_MANIFEST_FILE_NAME))?;
+        Ok(Pack { manifest, path })
+    }
+
+    /// Installs the pack at `source`, which is either a directory or a (gzipped) tarball.
+    ///
+    /// Unless `force` is set, installation fails if a pack with the same name is already installed
+    /// or if any of its shaders collide with existing shaders. Collisions allowed by `force` are
+    /// returned alongside the pack.
+    #[tracing::instrument(level = "debug")]
+    pub fn install(source: &Path, force: bool) -> Result<(Pack, Vec<Collision>), Error> {
+        let staging = StagingDir::create()?;
+
+        let (root, default_name) = if source.is_dir() {
+            let name = source
+                .canonicalize()
+                .ok()
+                .and_then(|p| p.file_name().map(ToOwned::to_owned));
+            (source.to_owned(), name)
+        } else {
+            let extract_dir = staging.path().join("extract");
+            extract_tarball(source, &extract_dir)?;
+            match unwrap_single_dir(&extract_dir)? {
+                Some(root) => {
+                    let name = root.file_name().map(ToOwned::to_owned);
+                    (root, name)
+                }
+                None => (extract_dir, tarball_name(source)),
+            }
+        };
+
+        let manifest_path = root.join(PACK_MANIFEST_FILE_NAME);
+        let mut manifest = if manifest_path.is_file() {
+            Manifest::read(&manifest_path)?
+        } else {
+            let name = default_name
+                .as_deref()
+                .map(|s| s.to_string_lossy().into_owned())
+                .ok_or_else(|| Error::NoName(source.to_owned()))?;
+            Manifest {
+                name,
+                version: None,
+                shaders: Vec::new(),
+                metadata: Default::default(),
+            }
+        };
+        validate_name(&manifest.name)?;
+
+        manifest.shaders = if manifest.shaders.is_empty() {
+            find_shader_files(&root)
+        } else {
+            validate_shader_files(&root, &manifest.shaders)?;
+            manifest.shaders
+        };
+        if manifest.shaders.is_empty() {
+            return Err(Error::NoShaders(source.to_owned()));
+        }
+        check_duplicate_names(&manifest.shaders)?;
+
+        let dest = Self::packs_dir().join(&manifest.name);
+        let existing = if dest.join(PACK_MANIFEST_FILE_NAME).is_file() {
+            if !force {
+                return Err(Error::AlreadyInstalled(manifest.name));
+            }
+            true
+        } else if dest.exists() {
+            return Err(Error::DestinationExists(dest));
+        } else {
+            false
+        };
+
+        let pack = Pack {
+            manifest,
+            path: dest,
+        };
+        let collisions = pack.collisions();
+        if !collisions.is_empty() && !force {
+            return Err(Error::Collisions {
+                name: pack.manifest.name,
+                collisions,
+            });
+        }
+
+        let build_dir = staging.path().join("pack");
+        for file in &pack.manifest.shaders {
+            copy_file(&root.join(file), &build_dir.join(file))?;
+            let sidecar = metadata::sidecar_path(file).filter(|path| root.join(path).is_file());
+            if let Some(sidecar) = sidecar {
+                copy_file(&root.join(&sidecar), &build_dir.join(&sidecar))?;
+            }
+        }
+        pack.manifest
+            .write(&build_dir.join(PACK_MANIFEST_FILE_NAME))?;
+
+        if existing {
+            fs::remove_dir_all(&pack.path).map_err(|source| Error::Io {
+                path: pack.path.clone(),
+                source,
+            })?;
+        }
+        create_parent_dir(&pack.path)?;
+        fs::rename(&build_dir, &pack.path).map_err(|source| Error::Io {
+            path: pack.path.clone(),
+            source,
+        })?;
+
+        Ok((pack, collisions))
+    }
+
+    /// Deletes the pack and all of its files.
+    pub fn remove(self) -> Result<(), Error> {
+        fs::remove_dir_all(&self.path).map_err(|source| Error::Io {
+            path: self.path,
+            source,
+        })
+    }
+
+    pub fn name(&self) -> &str {
+        &self.manifest.name
+    }
+
+    pub fn version(&self) -> Option<&str> {
+        self.manifest.version.as_deref()
+    }
+
+    /// Names by which the shaders in this pack are resolved
+    pub fn shader_names(&self) -> impl Iterator<Item = &OsStr> {
+        self.manifest
+            .shaders
+            .iter()
+            .filter_map(|file| PathExt::file_prefix(file.as_path()))
+    }
+
+    /// Finds builtins and shader files outside of this pack that resolve to the same name as one
+    /// of its shaders.
+    pub fn collisions(&self) -> Vec<Collision> {
+        let mut collisions = Vec::new();
+
+        for name in self.shader_names() {
+            if BuiltinShader::get(name.as_bytes()).is_some() {
+                collisions.push(Collision::Builtin {
+                    name: name.to_string_lossy().into_owned(),
+                });
+            }
+        }
+
+        for dir in shader_dirs() {
+            for path in shader_files(&dir) {
+                if path.starts_with(&self.path) {
+                    continue;
+                }
+                if let Some(name) = self
+                    .shader_names()
+                    .find(|&n| Some(n) == PathExt::file_prefix(path.as_path()))
+                {
+                    collisions.push(Collision::File {
+                        name: name.to_string_lossy().into_owned(),
+                        path,
+                    });
+                }
+            }
+        }
+
+        collisions
+    }
+}
+
+impl Manifest {
+    fn read(path: &Path) -> Result<Manifest, Error> {
+        let contents = fs::read_to_string(path).map_err(|source| Error::Io {
+            path: path.to_owned(),
+            source,
+        })?;
+        toml::from_str(&contents).map_err(|source| Error::ManifestParse {
+            path: path.to_owned(),
+            source,
+        })
+    }
+
+    fn write(&self, path: &Path) -> Result<(), Error> {
+        let contents = toml::to_string(self).map_err(|source| Error::ManifestSerialize {
+            path: path.to_owned(),
+            source,
+        })?;
+        fs::write(path, contents).map_err(|source| Error::Io {
+            path: path.to_owned(),
+            source,
+        })
+    }
+}
+
+impl fmt::Display for Collision {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Collision::Builtin { name } => write!(f, "{name:?} shadows the builtin shader"),
+            Collision::File { name, path } => write!(f, "{name:?} collides with {path:?}"),
+        }
+    }
+}
+
+/// Directory for assembling a pack before it is moved into place, removed when dropped
+struct StagingDir(PathBuf);
+
+impl StagingDir {
+    /// Creates the directory next to [`Pack::packs_dir`], so that the pack can be moved into
+    /// place without copying and without the resolver finding a partially written pack.
+    fn create() -> Result<StagingDir, Error> {
+        let path = HYPRSHADE_CONFIG_DIR.join(format!(".pack-staging-{}", process::id()));
+        if path.exists() {
+            fs::remove_dir_all(&path).map_err(|source| Error::Io {
+                path: path.clone(),
+                source,
+            })?;
+        }
+        fs::create_dir_all(&path).map_err(|source| Error::Io {
+            path: path.clone(),
+            source,
+        })?;
+        Ok(StagingDir(path))
+    }
+
+    fn path(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for StagingDir {
+    fn drop(&mut self) {
+        if let Err(err) = fs::remove_dir_all(&self.0) {
+            debug!("Failed to remove staging directory {:?}: {err}", self.0);
+        }
+    }
+}
+
+fn validate_name(name: &str) -> Result<(), Error> {
+    let is_valid = !name.is_empty()
+        && !name.starts_with('.')
+        && !name.contains(['/', '\0'])
+        && name != PACK_MANIFEST_FILE_NAME;
+    if is_valid {
+        Ok(())
+    } else {
+        Err(Error::InvalidName(name.to_owned()))
+    }
+}
+
+pub fn is_shader_file(path: &Path) -> bool {
+    let extension = match path.file_stem_extension() {
+        (Some(stem), Some(extension)) if extension == TEMPLATE_EXTENSION => {
+            Path::new(stem).extension()
+        }
+        (_, extension) => extension,
+    };
+    extension.is_some_and(|extension| SHADER_EXTENSIONS.iter().any(|e| extension == *e))
+}
+
+fn find_shader_files(root: &Path) -> Vec<PathBuf> {
+    let mut files = walk_shader_files(root)
+        .filter(|path| is_shader_file(path))
+        .filter_map(|path| path.strip_prefix(root).ok().map(ToOwned::to_owned))
+        .collect::<Vec<_>>();
+    files.sort();
+    files
+}
+
+fn validate_shader_files(root: &Path, files: &[PathBuf]) -> Result<(), Error> {
+    for file in files {
+        let is_contained = file
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)));
+        if !is_contained || !root.join(file).is_file() {
+            return Err(Error::MissingShader(file.to_owned()));
+        }
+    }
+    Ok(())
+}
+
+fn check_duplicate_names(files: &[PathBuf]) -> Result<(), Error> {
+    let mut seen: HashMap<&OsStr, &PathBuf> = HashMap::new();
+    for file in files {
+        let Some(name) = PathExt::file_prefix(file.as_path()) else {
+            continue;
+        };
+        if let Some(previous) = seen.insert(name, file) {
+            return Err(Error::DuplicateName {
+                name: name.to_string_lossy().into_owned(),
+                paths: (previous.to_owned(), file.to_owned()),
+            });
+        }
+    }
+    Ok(())
+}
+
+fn extract_tarball(source: &Path, dest: &Path) -> Result<(), Error> {
+    trace!("Extracting {source:?} into {dest:?}");
+
+    let io_error = |source_err| Error::Io {
+        path: source.to_owned(),
+        source: source_err,
+    };
+
+    let mut reader = BufReader::new(fs::File::open(source).map_err(io_error)?);
+    let mut magic = [0; 2];
+    let is_gzip = reader.read_exact(&mut magic).is_ok() && magic == GZIP_MAGIC;
+    reader.rewind().map_err(io_error)?;
+
+    let unpack = |reader: &mut dyn Read| -> io::Result<()> {
+        fs::create_dir_all(dest)?;
+        for entry in tar::Archive::new(reader).entries()? {
+            let mut entry = entry?;
+            let entry_type = entry.header().entry_type();
+            // Links could point anywhere, and copying the pack into place would follow them
+            if entry_type.is_file() || entry_type.is_dir() {
+                entry.unpack_in(dest)?;
+            } else {
+                debug!("Skipping {:?}, which is not a regular file", entry.path()?);
+            }
+        }
+        Ok(())
+    };
+    let result = if is_gzip {
+        unpack(&mut GzDecoder::new(reader))
+    } else {
+        unpack(&mut reader)
+    };
+    result.map_err(|source_err| Error::Extract {
+        path: source.to_owned(),
+        source: source_err,
+    })
+}
+
+/// Returns the only entry of `dir` if it is a directory, as is common for tarballs.
+fn unwrap_single_dir(dir: &Path) -> Result<Option<PathBuf>, Error> {
+    let entries = fs::read_dir(dir)
+        .and_then(|entries| entries.collect::<Result<Vec<_>, _>>())
+        .map_err(|source| Error::Io {
+            path: dir.to_owned(),
+            source,
+        })?;
+    match entries.as_slice() {
+        [entry] if entry.path().is_dir() => Ok(Some(entry.path())),
+        _ => Ok(None),
+    }
+}
+
+fn tarball_name(path: &Path) -> Option<OsString> {
+    let file_name = path.file_name()?.to_str()?;
+    let name = TARBALL_SUFFIXES
+        .iter()
+        .find_map(|suffix| file_name.strip_suffix(suffix))
+        .unwrap_or(file_name);
+    Some(name.into())
+}
+
+fn create_parent_dir(path: &Path) -> Result<(), Error> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|source| Error::Io {
+            path: parent.to_owned(),
+            source,
+        })?;
+    }
+    Ok(())
+}
+
+fn copy_file(from: &Path, to: &Path) -> Result<(), Error> {
+    create_parent_dir(to)?;
+    fs::copy(from, to).map(drop).map_err(|source| Error::Io {
+        path: from.to_owned(),
+        source,
+    })
+}
+
+#[non_exhaustive]
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("could not access {path:?}")]
+    Io { path: PathBuf, source: io::Error },
+    #[error("could not extract {path:?}")]
+    Extract { path: PathBuf, source: io::Error },
+    #[error("could not parse pack manifest {path:?}")]
+    ManifestParse {
+        path: PathBuf,
+        source: toml::de::Error,
+    },
+    #[error("could not serialize pack manifest {path:?}")]
+    ManifestSerialize {
+        path: PathBuf,
+        source: toml::ser::Error,
+    },
+    #[error("could not infer a pack name from {0:?}")]
+    NoName(PathBuf),
+    #[error("invalid pack name {0:?}")]
+    InvalidName(String),
+    #[error("no shaders found in {0:?}")]
+    NoShaders(PathBuf),
+    #[error("shader {0:?} listed in pack manifest does not exist")]
+    MissingShader(PathBuf),
+    #[error("more than one shader is named {name:?}: {:?} and {:?}", .paths.0, .paths.1)]
+    DuplicateName {
+        name: String,
+        paths: (PathBuf, PathBuf),
+    },
+    #[error("pack {0:?} is already installed")]
+    AlreadyInstalled(String),
+    #[error("{0:?} already exists and is not a pack")]
+    DestinationExists(PathBuf),
+    #[error("shaders in pack {name:?} collide with existing shaders")]
+    Collisions {
+        name: String,
+        collisions: Vec<Collision>,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shader_file_extensions() {
+        assert!(is_shader_file(Path::new("a/warm.glsl")));
+        assert!(is_shader_file(Path::new("warm.frag")));
+        assert!(is_shader_file(Path::new("warm.glsl.mustache")));
+        assert!(!is_shader_file(Path::new("README.md")));
+        assert!(!is_shader_file(Path::new("warm.mustache")));
+        assert!(!is_shader_file(Path::new(PACK_MANIFEST_FILE_NAME)));
+    }
+
+    #[test]
+    fn pack_names() {
+        assert!(validate_name("team-shaders").is_ok());
+        assert!(validate_name("").is_err());
+        assert!(validate_name(".hidden").is_err());
+        assert!(validate_name("..").is_err());
+        assert!(validate_name("a/b").is_err());
+    }
+
+    #[test]
+    fn names_from_tarballs() {
+        for (path, expected) in [
+            ("/tmp/team-1.0.tar.gz", "team-1.0"),
+            ("team.tgz", "team"),
+            ("team.tar", "team"),
+            ("team", "team"),
+        ] {
+            assert_eq!(tarball_name(Path::new(path)).unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn duplicate_names() {
+        let files = [PathBuf::from("warm.glsl"), PathBuf::from("sub/warm.frag")];
+        assert!(matches!(
+            check_duplicate_names(&files),
+            Err(Error::DuplicateName { name, .. }) if name == "warm"
+        ));
+        assert!(check_duplicate_names(&files[..1]).is_ok());
+    }
+
+    #[test]
+    fn manifest_round_trip() {
+        let manifest: Manifest = toml::from_str(
+            r#"
+                name = "team"
+                version = "1.2.0"
+                shaders = ["warm.glsl"]
+
+                [metadata]
+                author = "someone"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(manifest.name, "team");
+        assert_eq!(manifest.shaders, [PathBuf::from("warm.glsl")]);
+
+        let s = toml::to_string(&manifest).unwrap();
+        assert_eq!(toml::from_str::<Manifest>(&s).unwrap(), manifest);
+
+        let minimal: Manifest = toml::from_str(r#"name = "team""#).unwrap();
+        assert_eq!(minimal.version, None);
+        assert!(minimal.shaders.is_empty() && minimal.metadata.is_empty());
+    }
+}
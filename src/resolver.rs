@@ -1,16 +1,21 @@
 use std::{
+    collections::BTreeMap,
     ffi::OsStr,
     fs, io,
     path::{MAIN_SEPARATOR, Path, PathBuf},
 };
 
-use tracing::{debug, trace};
+use tracing::{debug, trace, warn};
 use walkdir::WalkDir;
 
-use crate::{builtin::BuiltinShader, dirs::shader_dirs, shader::Shader, util::PathExt};
+use crate::{
+    builtin::BuiltinShader, dirs::shader_dirs, index::ShaderIndex, metadata::SIDECAR_EXTENSION,
+    shader::Shader, util::PathExt,
+};
 
-const MAX_DEPTH: usize = 10;
+pub const MAX_DEPTH: usize = 10;
 
+/// Finds a shader by name in the shader directories, or by path
 pub struct Resolver<'a>(ResolverInner<'a>);
 
 enum ResolverInner<'a> {
@@ -22,6 +27,7 @@ struct ResolverWithPath<'a>(&'a Path);
 struct ResolverWithName<'a>(&'a OsStr);
 
 impl<'a> Resolver<'a> {
+    /// Treats `shader` as a path if it contains a separator, or else as a name.
     pub fn with_cli_arg(shader: &'a str) -> Self {
         if shader.contains(MAIN_SEPARATOR) {
             Self::with_path(Path::new(shader))
@@ -30,20 +36,48 @@ impl<'a> Resolver<'a> {
         }
     }
 
+    /// Resolves the shader file at `path`.
     pub fn with_path(path: &'a Path) -> Self {
         Self(ResolverInner::WithPath(ResolverWithPath(path)))
     }
 
+    /// Resolves the shader named `name`, e.g. `vibrance` for `vibrance.glsl.mustache`.
     pub fn with_name<S: ?Sized + AsRef<OsStr>>(name: &'a S) -> Self {
         Self(ResolverInner::WithName(ResolverWithName(name.as_ref())))
     }
 
+    /// Returns the shader found first, searching shader directories before builtin shaders.
     pub fn resolve(&self) -> Result<Shader, Error> {
         match &self.0 {
             ResolverInner::WithPath(r) => Ok(r.resolve()?),
             ResolverInner::WithName(r) => Ok(r.resolve()?),
         }
     }
+
+    /// Lists every shader matching a name in the order they are tried, so the first one is what
+    /// [`Resolver::resolve`] picks. Paths have no other candidates.
+    pub fn candidates(&self) -> Vec<Candidate> {
+        match &self.0 {
+            ResolverInner::WithPath(ResolverWithPath(path)) => match path.parent() {
+                Some(dir) if path.is_file() => vec![Candidate::File {
+                    dir: dir.to_owned(),
+                    path: path.to_path_buf(),
+                }],
+                _ => Vec::new(),
+            },
+            ResolverInner::WithName(r) => r.candidates(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Candidate {
+    File {
+        /// Shader directory in which the file was found
+        dir: PathBuf,
+        path: PathBuf,
+    },
+    Builtin(BuiltinShader),
 }
 
 impl ResolverWithPath<'_> {
@@ -72,11 +106,21 @@ impl ResolverWithName<'_> {
         let Self(name) = &self;
 
         for dir in shader_dirs() {
-            if let Some(path) = self.resolve_in(&dir) {
+            let matches = self.resolve_in(&dir);
+            if let Some((path, others)) = matches.split_first() {
+                if !others.is_empty() {
+                    let relative = |p: &PathBuf| p.strip_prefix(&dir).unwrap_or(p).to_owned();
+                    warn!(
+                        "More than one shader named {:?} in {dir:?}, using {:?} over {:?}",
+                        name.to_string_lossy(),
+                        relative(path),
+                        others.iter().map(relative).collect::<Vec<_>>()
+                    );
+                }
                 let path =
-                    fs::canonicalize(&path).map_err(|source| ErrorFromName::Canonicalize {
+                    fs::canonicalize(path).map_err(|source| ErrorFromName::Canonicalize {
                         name: name.to_string_lossy().into_owned(),
-                        path,
+                        path: path.to_owned(),
                         source,
                     })?;
                 trace!("Resolved {name:?} to {path:?}");
@@ -94,41 +138,131 @@ impl ResolverWithName<'_> {
         ))
     }
 
+    fn candidates(&self) -> Vec<Candidate> {
+        let Self(name) = *self;
+
+        shader_dirs()
+            .into_iter()
+            .flat_map(|dir| {
+                self.resolve_in(&dir)
+                    .into_iter()
+                    .map(move |path| Candidate::File {
+                        dir: dir.clone(),
+                        path,
+                    })
+            })
+            .chain(BuiltinShader::get(name.as_encoded_bytes()).map(Candidate::Builtin))
+            .collect()
+    }
+
+    /// Finds all files in `dir` matching the name, with the one that takes precedence first.
+    ///
+    /// Files closer to `dir` take precedence, followed by files whose path sorts first.
     #[tracing::instrument(level = "debug", skip(self), fields(name = ?self.0.to_string_lossy(), ?dir))]
-    fn resolve_in(&self, dir: &Path) -> Option<PathBuf> {
+    fn resolve_in(&self, dir: &Path) -> Vec<PathBuf> {
         let Self(name) = *self;
 
         if !dir.is_dir() {
             debug!("Not a directory: {dir:?}");
-            return None;
+            return Vec::new();
         }
 
         trace!("Searching for {name:?} in {dir:?}");
 
-        for entry in WalkDir::new(dir)
-            .max_depth(MAX_DEPTH)
+        let mut matches = shader_files(dir)
             .into_iter()
-            .filter_map(|e| {
-                e.inspect_err(|err| {
-                    debug!("Ignoring error encountered when walking directory {dir:?}");
-                    debug!(?err);
-                })
-                .ok()
-                .and_then(|e| e.file_type().is_file().then_some(e))
+            .filter(|path| {
+                trace!("Checking {path:?}");
+                PathExt::file_prefix(path.as_path()) == Some(name)
             })
-        {
-            trace!("Checking {entry:?}");
+            .inspect(|path| trace!("Entry {path:?} matches {name:?}"))
+            .collect::<Vec<_>>();
+        matches.sort_by(|a, b| (a.components().count(), a).cmp(&(b.components().count(), b)));
 
-            let prefix = PathExt::file_prefix(entry.path());
-            if Some(name) == prefix {
-                trace!("Entry matches {name:?}");
+        matches
+    }
+}
 
-                return Some(entry.into_path());
-            }
-        }
+/// Lists files in `dir` that may be resolved by name, in the order they are searched.
+///
+/// Shader directories are listed from the [`ShaderIndex`], anything else is walked.
+pub fn shader_files(dir: &Path) -> Vec<PathBuf> {
+    match ShaderIndex::get().files_in(dir) {
+        Some(files) => files.to_vec(),
+        None => walk_shader_files(dir).collect(),
+    }
+}
+
+/// Walks `dir` for files that may be resolved by name, in the order they are searched.
+pub fn walk_shader_files(dir: &Path) -> impl Iterator<Item = PathBuf> {
+    WalkDir::new(dir)
+        .max_depth(MAX_DEPTH)
+        .into_iter()
+        .filter_map(move |e| {
+            e.inspect_err(|err| {
+                debug!("Ignoring error encountered when walking directory {dir:?}");
+                debug!(?err);
+            })
+            .ok()
+            .and_then(|e| e.file_type().is_file().then_some(e))
+            .filter(|e| is_resolvable(e.path()))
+        })
+        .map(|e| e.into_path())
+}
+
+/// Metadata sidecars and pack manifests are skipped, since they share a name with shaders.
+pub fn is_resolvable(path: &Path) -> bool {
+    path.extension().is_none_or(|ext| ext != SIDECAR_EXTENSION)
+}
 
-        None
+/// Every shader that can be resolved by name, with the one a name resolves to.
+pub fn available_shaders() -> BTreeMap<String, Candidate> {
+    let mut shaders = BTreeMap::new();
+
+    for dir in shader_dirs() {
+        let mut files = shader_files(&dir);
+        files.sort_by(|a, b| (a.components().count(), a).cmp(&(b.components().count(), b)));
+        for path in files {
+            let Some(name) = PathExt::file_prefix(path.as_path()) else {
+                continue;
+            };
+            shaders
+                .entry(name.to_string_lossy().into_owned())
+                .or_insert_with(|| Candidate::File {
+                    dir: dir.clone(),
+                    path: path.clone(),
+                });
+        }
     }
+    for builtin in BuiltinShader::all() {
+        shaders
+            .entry(builtin.name().to_owned())
+            .or_insert(Candidate::Builtin(builtin));
+    }
+
+    shaders
+}
+
+/// Names of available shaders similar to `name`, closest first.
+pub fn similar_names(name: &str) -> Vec<String> {
+    const MAX_SIMILAR_NAMES: usize = 3;
+    const MIN_SIMILARITY: f64 = 0.8;
+
+    let mut similar = available_shaders()
+        .into_keys()
+        .filter_map(|candidate| {
+            let similarity = strsim::jaro_winkler(name, &candidate);
+            (similarity >= MIN_SIMILARITY || candidate.contains(name))
+                .then_some((similarity, candidate))
+        })
+        .collect::<Vec<_>>();
+    similar.sort_by(|(a, a_name), (b, b_name)| b.total_cmp(a).then_with(|| a_name.cmp(b_name)));
+
+    similar
+        .into_iter()
+        .take(MAX_SIMILAR_NAMES)
+        .map(|(_, candidate)| candidate)
+        .collect()
 }
 
 #[non_exhaustive]
@@ -162,6 +296,16 @@ pub enum ErrorFromName {
     },
 }
 
+impl Error {
+    /// Names of available shaders similar to the one that wasn't found, if that's the error.
+    pub fn similar_names(&self) -> Vec<String> {
+        match self {
+            Error::FromName(ErrorFromName::ShaderNameNotFound(name)) => similar_names(name),
+            _ => Vec::new(),
+        }
+    }
+}
+
 impl ErrorFromPath {
     fn io_error_not_found(path: PathBuf) -> Self {
         Self::IoError(
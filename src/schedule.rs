@@ -1,6 +1,6 @@
 use std::cmp::Ordering;
 
-use chrono::NaiveTime;
+use chrono::{DateTime, LocalResult, NaiveTime, Offset, TimeDelta, TimeZone, Utc};
 
 use crate::{
     config::{Config, Shader as ShaderConfig},
@@ -8,6 +8,7 @@ use crate::{
     shader::Shader,
 };
 
+/// Which shader the configuration schedules at what time of day
 pub struct Schedule<'a> {
     table: ScheduleTable<'a, ShaderConfig>,
     config: &'a Config,
@@ -21,15 +22,68 @@ impl<'a> Schedule<'a> {
         }
     }
 
-    pub fn scheduled_shader(&self, time: &NaiveTime) -> Result<Option<Shader>, resolver::Error> {
+    /// Returns the shader scheduled at `now`, in the configured time zone if there is one.
+    pub fn scheduled_shader<Tz: TimeZone>(
+        &self,
+        now: &DateTime<Tz>,
+    ) -> Result<Option<Shader>, resolver::Error> {
+        let now = now.to_utc();
+        let time = match self.config.timezone() {
+            Some(timezone) => time_of_day(&now, &timezone),
+            None => time_of_day(&now, &chrono::Local),
+        };
         self.table
-            .get(time)
+            .get(&time)
             .or_else(|| self.config.default_shader())
             .map(|s| Resolver::with_name(&s.name).resolve())
             .transpose()
     }
 }
 
+/// Returns the time of day the schedule is looked up at when it is `now` in `timezone`.
+///
+/// On days the clocks change, entries are looked up as follows:
+///
+/// * When clocks go forward, times in the skipped period never occur, so entries starting in it
+///   start as soon as the clocks have gone forward.
+/// * When clocks go back, times in the repeated period occur twice. Entries starting in it start
+///   the first time, and the schedule stays as it was just before the clocks went back until the
+///   repeated period is over, so nothing starts twice.
+fn time_of_day<Tz: TimeZone>(now: &DateTime<Utc>, timezone: &Tz) -> NaiveTime {
+    let local = now.with_timezone(timezone);
+    let LocalResult::Ambiguous(earliest, _) = timezone.from_local_datetime(&local.naive_local())
+    else {
+        return local.time();
+    };
+    if earliest == local {
+        return local.time();
+    }
+
+    // The clocks went back at some whole second since the same time of day first occurred, which
+    // was still before the change.
+    let offset_now = local.offset().fix();
+    let is_after_change = |timestamp: i64| {
+        let instant = DateTime::from_timestamp(timestamp, 0).expect("timestamp should be in range");
+        timezone
+            .offset_from_utc_datetime(&instant.naive_utc())
+            .fix()
+            == offset_now
+    };
+    let (mut before, mut after) = (earliest.timestamp(), now.timestamp());
+    while after - before > 1 {
+        let middle = before + (after - before) / 2;
+        if is_after_change(middle) {
+            after = middle;
+        } else {
+            before = middle;
+        }
+    }
+    let change = DateTime::from_timestamp(after, 0).expect("timestamp should be in range");
+    (change - TimeDelta::nanoseconds(1))
+        .with_timezone(timezone)
+        .time()
+}
+
 struct ScheduleTable<'a, T> {
     events: Vec<(TimeRange, &'a T)>,
 }
@@ -136,3 +190,44 @@ impl From<(NaiveTime, NaiveTime)> for TimeRange {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use chrono_tz::Europe::Berlin;
+
+    use super::*;
+
+    fn time_in_berlin(utc: &str) -> NaiveTime {
+        time_of_day(&utc.parse().unwrap(), &Berlin)
+    }
+
+    fn hms(time: &str) -> NaiveTime {
+        time.parse().unwrap()
+    }
+
+    #[test]
+    fn ordinary_day() {
+        assert_eq!(time_in_berlin("2026-06-01T10:30:00Z"), hms("12:30:00"));
+    }
+
+    #[test]
+    fn clocks_go_forward() {
+        // 02:00 CET becomes 03:00 CEST
+        assert_eq!(time_in_berlin("2026-03-29T00:59:59Z"), hms("01:59:59"));
+        assert_eq!(time_in_berlin("2026-03-29T01:00:00Z"), hms("03:00:00"));
+    }
+
+    #[test]
+    fn clocks_go_back() {
+        // 03:00 CEST becomes 02:00 CET
+        assert_eq!(time_in_berlin("2026-10-25T00:30:00Z"), hms("02:30:00"));
+        assert_eq!(time_in_berlin("2026-10-25T00:59:59Z"), hms("02:59:59"));
+
+        let repeated = hms("02:59:59.999999999");
+        assert_eq!(time_in_berlin("2026-10-25T01:00:00Z"), repeated);
+        assert_eq!(time_in_berlin("2026-10-25T01:30:00Z"), repeated);
+        assert_eq!(time_in_berlin("2026-10-25T01:59:59Z"), repeated);
+
+        assert_eq!(time_in_berlin("2026-10-25T02:00:00Z"), hms("03:00:00"));
+    }
+}
@@ -1,23 +1,28 @@
 use std::{
-    fs::{self, File},
-    io,
+    fs, io,
     os::unix::ffi::OsStrExt,
     path::{Path, PathBuf},
 };
 
 use serde::{Deserialize, Serialize};
+use tracing::debug;
 
 use crate::{
-    builtin::BuiltinShader,
+    builtin::{self, BuiltinShader},
+    compositor::Compositor,
     constants::HYPRSHADE_RUNTIME_DIR,
-    hyprctl,
+    lock::{self, RuntimeLock},
+    metadata,
     resolver::{self, Resolver},
-    template::TemplateDataMap,
-    util::{PathExt, make_runtime_path},
+    template::{MergeDeep, TemplateDataMap},
+    util::{PathExt, make_runtime_path, write_atomic},
 };
 
-const TEMPLATE_EXTENSION: &str = "mustache";
+pub const TEMPLATE_EXTENSION: &str = "mustache";
 
+const LAST_FILE_NAME: &str = "last.json";
+
+/// A shader that can be turned on, either a file found by [`Resolver`] or a builtin shader
 #[derive(Debug, Clone)]
 pub struct Shader(ShaderInner);
 
@@ -27,14 +32,22 @@ enum ShaderInner {
     Builtin(BuiltinShader),
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// A shader as it was turned on, with the data its template was rendered with
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ShaderInstance {
     source: ShaderSource,
     instance_path: PathBuf,
     data: TemplateDataMap,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Contents of a shader rendered from its template, and the path it is loaded from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rendered {
+    pub path: PathBuf,
+    pub contents: Vec<u8>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 enum ShaderSource {
     Path(PathBuf),
     Builtin(String),
@@ -53,8 +66,12 @@ impl Shader {
         Self(ShaderInner::Builtin(builtin_shader))
     }
 
-    pub fn current() -> eyre::Result<Option<ShaderInstance>> {
-        match hyprctl::shader::get()? {
+    /// Returns the shader `compositor` has loaded, if any.
+    pub fn current<C: Compositor>(compositor: &C) -> Result<Option<ShaderInstance>, Error> {
+        match compositor
+            .screen_shader()
+            .map_err(|err| Error::Current(err.into()))?
+        {
             Some(path) => {
                 if path.starts_with(*HYPRSHADE_RUNTIME_DIR) {
                     Ok(Some(ShaderInstance::read_alongside_shader(&path)?))
@@ -66,50 +83,196 @@ impl Shader {
         }
     }
 
-    pub fn off() -> eyre::Result<()> {
-        hyprctl::shader::clear()
+    /// Turns off whatever shader `compositor` has loaded.
+    pub fn off<C: Compositor>(compositor: &C) -> Result<(), Error> {
+        Shader::off_remembering(compositor, false)
+    }
+
+    /// Same as [`Shader::off`], also forgetting the last shader for `hyprshade restore` if
+    /// `remember_last` is set, which only the command line tool does.
+    pub(crate) fn off_remembering<C: Compositor>(
+        compositor: &C,
+        remember_last: bool,
+    ) -> Result<(), Error> {
+        let _lock = RuntimeLock::acquire()?;
+        compositor
+            .clear_screen_shader()
+            .map_err(|err| Error::Clear(err.into()))?;
+        if remember_last {
+            ShaderInstance::forget_last()?;
+        }
+        Ok(())
+    }
+
+    /// Renders the shader with `data` if it is a template, then has `compositor` load it.
+    pub fn on<C: Compositor>(&self, compositor: &C, data: &TemplateDataMap) -> Result<(), Error> {
+        self.on_remembering(compositor, data, false)
+    }
+
+    /// Same as [`Shader::on`], also remembering the shader for `hyprshade restore` if
+    /// `remember_last` is set.
+    pub(crate) fn on_remembering<C: Compositor>(
+        &self,
+        compositor: &C,
+        data: &TemplateDataMap,
+        remember_last: bool,
+    ) -> Result<(), Error> {
+        let _lock = RuntimeLock::acquire()?;
+        let data = &self.absolutize_paths(data);
+        let rendered = self.render(data)?;
+        self.apply(compositor, data, rendered, remember_last)
+    }
+
+    /// Turns on the shader unless it is already on with `data` and its rendered file is up to
+    /// date, since reloading a shader makes Hyprland recompile it. Returns whether it was turned on.
+    pub fn on_if_changed<C: Compositor>(
+        &self,
+        compositor: &C,
+        data: &TemplateDataMap,
+    ) -> Result<bool, Error> {
+        self.on_if_changed_remembering(compositor, data, false)
+    }
+
+    /// Same as [`Shader::on_if_changed`], also remembering the shader for `hyprshade restore` if
+    /// `remember_last` is set and it was turned on.
+    pub(crate) fn on_if_changed_remembering<C: Compositor>(
+        &self,
+        compositor: &C,
+        data: &TemplateDataMap,
+        remember_last: bool,
+    ) -> Result<bool, Error> {
+        let _lock = RuntimeLock::acquire()?;
+        let data = &self.absolutize_paths(data);
+        let rendered = self.render(data)?;
+        // Whatever is on can't be compared if its instance can't be read, so it is replaced
+        let current = Shader::current(compositor).unwrap_or_else(|err| {
+            debug!("Failed to read current shader: {err:?}");
+            None
+        });
+        if let Some(current) = current {
+            let is_same = current.source == ShaderSource::from(self.0.clone())
+                && (current.data == *data || rendered.is_none())
+                && match &rendered {
+                    Some(rendered) => current.is_loaded(rendered),
+                    None => self.path() == Some(current.instance_path()),
+                };
+            if is_same {
+                debug!("Shader {:?} is already on", self.name());
+                return Ok(false);
+            }
+        }
+        self.apply(compositor, data, rendered, remember_last)?;
+        Ok(true)
     }
 
-    pub fn on(&self, data: &TemplateDataMap) -> eyre::Result<()> {
-        let path: PathBuf = match &self.0 {
+    fn apply<C: Compositor>(
+        &self,
+        compositor: &C,
+        data: &TemplateDataMap,
+        rendered: Option<Rendered>,
+        remember_last: bool,
+    ) -> Result<(), Error> {
+        let set = |path: &Path| {
+            compositor
+                .set_screen_shader(path)
+                .map_err(|err| Error::Set(err.into()))
+        };
+        let instance = match rendered {
+            Some(Rendered { path, contents }) => {
+                write_atomic(&path, contents).map_err(|source| Error::Io {
+                    path: path.clone(),
+                    source,
+                })?;
+
+                // Written before Hyprland loads the shader, so the instance always describes
+                // what's loaded
+                let instance = ShaderInstance {
+                    source: self.0.clone().into(),
+                    instance_path: path,
+                    data: data.to_owned(),
+                };
+                instance.write_alongside_shader()?;
+
+                set(&instance.instance_path)?;
+                instance
+            }
+            None => {
+                let ShaderInner::Path(path) = &self.0 else {
+                    unreachable!("builtin shaders are always rendered")
+                };
+                set(path)?;
+                ShaderInstance::from_path_buf(path.to_owned())
+            }
+        };
+
+        if remember_last {
+            instance.write_last()?;
+        }
+        Ok(())
+    }
+
+    /// Returns `data` with relative paths made absolute, so that the instance can be rendered again
+    /// from another directory.
+    fn absolutize_paths(&self, data: &TemplateDataMap) -> TemplateDataMap {
+        let mut data = data.clone();
+        if let ShaderInner::Builtin(builtin_shader) = &self.0 {
+            builtin_shader.absolutize_paths(&mut data);
+        }
+        data
+    }
+
+    /// Renders the shader into the path it should be loaded from, or returns [`None`] if the
+    /// source can be loaded as is. Nothing is written.
+    pub fn render(&self, data: &TemplateDataMap) -> Result<Option<Rendered>, Error> {
+        let mut contents = Vec::new();
+        let out_path = match &self.0 {
             ShaderInner::Path(path) => match path.file_stem_extension() {
                 (Some(stem), Some(extension)) if extension == TEMPLATE_EXTENSION => {
                     let template = mustache::compile_path(path)?;
-                    let out_path = make_runtime_path(stem)?;
-                    let mut out_file = File::create(&out_path)?;
-                    template.render(&mut out_file, data)?;
-                    out_path
-                }
-                _ => {
-                    hyprctl::shader::set(path)?;
-                    return Ok(());
+                    let data = {
+                        let mut data = data.clone();
+                        data.merge_deep_keep(metadata::read_defaults(path)?);
+                        data
+                    };
+                    template.render_data(&mut contents, &data.to_mustache_data())?;
+                    runtime_path(stem)?
                 }
+                _ => return Ok(None),
             },
             ShaderInner::Builtin(builtin_shader) => {
-                let file_name = format!("{}.glsl", builtin_shader.name());
-                let out_path = make_runtime_path(file_name)?;
-                let mut out_file = File::create(&out_path)?;
                 if builtin_shader.is_template() {
-                    builtin_shader.render(&mut out_file, data)?;
-                    out_path
+                    builtin_shader.render(&mut contents, data)?;
                 } else {
-                    builtin_shader.write(&mut out_file)?;
-                    out_path
+                    builtin_shader
+                        .write(&mut contents)
+                        .expect("writing to a Vec never fails");
                 }
+                runtime_path(format!("{}.glsl", builtin_shader.name()))?
             }
         };
-        hyprctl::shader::set(&path)?;
+        Ok(Some(Rendered {
+            path: out_path,
+            contents,
+        }))
+    }
 
-        let instance = ShaderInstance {
-            source: self.0.clone().into(),
-            instance_path: path,
-            data: data.to_owned(),
-        };
-        instance.write_alongside_shader()?;
+    /// Describes the shader and its variables, in the format of metadata sidecars.
+    pub fn metadata(&self) -> Result<toml::Table, metadata::Error> {
+        match &self.0 {
+            ShaderInner::Path(path) => metadata::read(path),
+            ShaderInner::Builtin(builtin) => Ok(builtin.metadata_toml()),
+        }
+    }
 
-        Ok(())
+    /// Path of the shader file, unless it is builtin
+    pub fn path(&self) -> Option<&Path> {
+        match &self.0 {
+            ShaderInner::Path(path) => Some(path),
+            ShaderInner::Builtin(_) => None,
+        }
     }
 
+    /// Name the shader is resolved by, e.g. `vibrance` for `vibrance.glsl.mustache`
     pub fn name(&self) -> &str {
         match &self.0 {
             ShaderInner::Path(path) => {
@@ -144,7 +307,12 @@ impl TryFrom<ShaderInstance> for Shader {
 
 impl ShaderInstance {
     fn read_alongside_shader(path: &Path) -> Result<ShaderInstance, ReadShaderInstanceError> {
-        let path = ShaderInstance::path_from_instance_path(path);
+        ShaderInstance::read(&ShaderInstance::path_from_instance_path(path))
+    }
+
+    /// Reads an instance from the `.json` file written alongside the file Hyprland loads.
+    pub fn read(path: &Path) -> Result<ShaderInstance, ReadShaderInstanceError> {
+        let path = path.to_owned();
         let s = fs::read_to_string(&path).map_err(|source| ReadShaderInstanceError::Io {
             path: path.clone(),
             source,
@@ -157,11 +325,12 @@ impl ShaderInstance {
 
     fn write_alongside_shader(&self) -> Result<(), WriteShaderInstanceError> {
         let path = ShaderInstance::path_from_instance_path(&self.instance_path);
-        let file = File::create(&path).map_err(|source| WriteShaderInstanceError::Io {
-            path: path.clone(),
-            source,
-        })?;
-        serde_json::to_writer(file, &self).map_err(|source| WriteShaderInstanceError::SerdeJson {
+        let contents =
+            serde_json::to_vec(&self).map_err(|source| WriteShaderInstanceError::SerdeJson {
+                path: path.clone(),
+                source,
+            })?;
+        write_atomic(&path, contents).map_err(|source| WriteShaderInstanceError::Io {
             path: path.clone(),
             source,
         })
@@ -171,12 +340,64 @@ impl ShaderInstance {
         instance_path.with_extension("json")
     }
 
-    #[allow(dead_code)]
-    pub fn restore(self) -> eyre::Result<()> {
+    /// Turns the instance back on, unless it is already on.
+    pub fn restore<C: Compositor>(self, compositor: &C) -> Result<(), Error> {
+        self.restore_remembering(compositor, false)
+    }
+
+    /// Same as [`ShaderInstance::restore`], also remembering the shader for `hyprshade restore`
+    /// if `remember_last` is set.
+    pub(crate) fn restore_remembering<C: Compositor>(
+        self,
+        compositor: &C,
+        remember_last: bool,
+    ) -> Result<(), Error> {
         let shader = self.to_shader()?;
-        shader.on(&self.data)
+        shader.on_if_changed_remembering(compositor, &self.data, remember_last)?;
+        Ok(())
+    }
+
+    /// The shader turned on last, unless the shader was turned off since. Unlike
+    /// [`Shader::current`], this survives Hyprland reloading its configuration.
+    pub fn last() -> Result<Option<ShaderInstance>, Error> {
+        let path = runtime_path(LAST_FILE_NAME)?;
+        match ShaderInstance::read(&path) {
+            Ok(instance) => Ok(Some(instance)),
+            Err(ReadShaderInstanceError::Io { source, .. })
+                if source.kind() == io::ErrorKind::NotFound =>
+            {
+                Ok(None)
+            }
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    fn write_last(&self) -> Result<(), Error> {
+        let path = runtime_path(LAST_FILE_NAME)?;
+        let contents =
+            serde_json::to_vec(self).map_err(|source| WriteShaderInstanceError::SerdeJson {
+                path: path.clone(),
+                source,
+            })?;
+        write_atomic(&path, contents)
+            .map_err(|source| WriteShaderInstanceError::Io { path, source }.into())
+    }
+
+    fn forget_last() -> Result<(), Error> {
+        let path = runtime_path(LAST_FILE_NAME)?;
+        match fs::remove_file(&path) {
+            Ok(()) => Ok(()),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(()),
+            Err(source) => Err(Error::Io { path, source }),
+        }
+    }
+
+    /// Path of the `.json` file describing the instance, written alongside the file Hyprland loads
+    pub fn sidecar_path(&self) -> PathBuf {
+        ShaderInstance::path_from_instance_path(&self.instance_path)
     }
 
+    /// Describes a shader file loaded as is, like one that isn't a template.
     pub fn from_path_buf(path: PathBuf) -> ShaderInstance {
         ShaderInstance {
             source: ShaderSource::Path(path.clone()),
@@ -185,6 +406,32 @@ impl ShaderInstance {
         }
     }
 
+    /// Data the shader was rendered with, excluding defaults
+    pub fn data(&self) -> &TemplateDataMap {
+        &self.data
+    }
+
+    /// Path of the file Hyprland has loaded, which is rendered from the source for templates
+    pub fn instance_path(&self) -> &Path {
+        &self.instance_path
+    }
+
+    /// Whether the loaded file is what the source renders to with the same data, which is not the
+    /// case if the source was edited since.
+    pub fn is_up_to_date(&self) -> Result<bool, Error> {
+        let shader = self.to_shader()?;
+        let Some(rendered) = shader.render(&self.data)? else {
+            return Ok(shader.path() == Some(&self.instance_path));
+        };
+        Ok(self.is_loaded(&rendered))
+    }
+
+    fn is_loaded(&self, rendered: &Rendered) -> bool {
+        self.instance_path == rendered.path
+            && fs::read(&self.instance_path).is_ok_and(|loaded| loaded == rendered.contents)
+    }
+
+    /// Resolves the shader the instance was rendered from.
     pub fn to_shader(&self) -> Result<Shader, ShaderConversionError> {
         match &self.source {
             ShaderSource::Path(path) => Resolver::with_path(path).resolve().map_err(|source| {
@@ -205,7 +452,7 @@ impl ShaderInstance {
 
 #[derive(Debug, thiserror::Error)]
 #[error("reading shader instance from {path:?}")]
-enum ReadShaderInstanceError {
+pub enum ReadShaderInstanceError {
     Io {
         path: PathBuf,
         source: io::Error,
@@ -218,7 +465,7 @@ enum ReadShaderInstanceError {
 
 #[derive(Debug, thiserror::Error)]
 #[error("writing shader instance to {path:?}")]
-enum WriteShaderInstanceError {
+pub enum WriteShaderInstanceError {
     Io {
         path: PathBuf,
         source: io::Error,
@@ -239,6 +486,40 @@ pub enum ShaderConversionError {
     },
 }
 
+/// Error turning a shader on or off, which holds the [`Compositor::Error`] if applying it failed
+#[non_exhaustive]
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("failed to find current screen shader")]
+    Current(#[source] Box<dyn std::error::Error + Send + Sync>),
+    #[error("failed to set screen shader")]
+    Set(#[source] Box<dyn std::error::Error + Send + Sync>),
+    #[error("failed to turn off screen shader")]
+    Clear(#[source] Box<dyn std::error::Error + Send + Sync>),
+    #[error(transparent)]
+    Template(#[from] mustache::Error),
+    #[error(transparent)]
+    Metadata(#[from] metadata::Error),
+    #[error(transparent)]
+    Builtin(#[from] builtin::RenderError),
+    #[error(transparent)]
+    ReadInstance(#[from] ReadShaderInstanceError),
+    #[error(transparent)]
+    WriteInstance(#[from] WriteShaderInstanceError),
+    #[error(transparent)]
+    Conversion(#[from] ShaderConversionError),
+    #[error("failed to create runtime directory")]
+    RuntimeDir(#[source] io::Error),
+    #[error("failed to write {path:?}")]
+    Io { path: PathBuf, source: io::Error },
+    #[error(transparent)]
+    Lock(#[from] lock::Error),
+}
+
+fn runtime_path<P: AsRef<Path>>(file_name: P) -> Result<PathBuf, Error> {
+    make_runtime_path(file_name).map_err(Error::RuntimeDir)
+}
+
 impl From<ShaderInner> for ShaderSource {
     fn from(value: ShaderInner) -> Self {
         match value {
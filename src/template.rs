@@ -1,6 +1,6 @@
 use std::{collections::HashMap, str::FromStr};
 
-use serde::{Deserialize, Serialize, ser};
+use serde::{Deserialize, Serialize};
 
 pub trait MergeDeep<A> {
     #[allow(dead_code)]
@@ -15,16 +15,19 @@ pub trait MergeDeep<A> {
     fn merge_deep_force<T: IntoIterator<Item = A>>(&mut self, iter: T);
 }
 
+/// Variables a shader template is rendered with, e.g. `balance.red = 0.5`
 #[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
 #[serde(transparent)]
 pub struct TemplateDataMap(HashMap<String, TemplateData>);
 
+/// Value of a template variable, or a table of them
+#[non_exhaustive]
 #[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 #[serde(untagged)]
 pub enum TemplateData {
-    #[serde(serialize_with = "TemplateData::serialize_enum")]
     Enum(String),
     Float(f64),
+    Bool(bool),
     Map(HashMap<String, TemplateData>),
 }
 
@@ -36,6 +39,38 @@ impl TemplateDataMap {
     pub fn new() -> Self {
         Self::default()
     }
+
+    /// Converts into data for rendering. See [`TemplateData::to_mustache_data`].
+    pub fn to_mustache_data(&self) -> mustache::Data {
+        mustache::Data::Map(
+            self.0
+                .iter()
+                .map(|(k, v)| (k.to_owned(), v.to_mustache_data()))
+                .collect(),
+        )
+    }
+
+    /// Returns the value at the nested key `path` (e.g. `["balance", "red"]`).
+    pub fn get_path<S: AsRef<str>>(&self, path: &[S]) -> Option<&TemplateData> {
+        let (first, rest) = path.split_first()?;
+        rest.iter()
+            .try_fold(self.0.get(first.as_ref())?, |data, key| match data {
+                TemplateData::Map(map) => map.get(key.as_ref()),
+                _ => None,
+            })
+    }
+
+    /// Sets the value at the nested key `path`, creating intermediate maps as needed.
+    pub fn insert_path<S: AsRef<str>>(&mut self, path: &[S], value: TemplateData) {
+        let Some((leaf_key, rest)) = path.split_last() else {
+            return;
+        };
+        let data = rest.iter().rev().fold(
+            TemplateDataMap::from([(leaf_key.as_ref().to_owned(), value)]),
+            |map, key| TemplateDataMap::from([(key.as_ref().to_owned(), TemplateData::from(map))]),
+        );
+        self.merge_deep_force(data);
+    }
 }
 
 impl MergeDeep<(String, TemplateData)> for HashMap<String, TemplateData> {
@@ -134,15 +169,35 @@ impl TemplateData {
         value.parse::<TemplateDataFromCliArg>().map(|v| v.value)
     }
 
-    fn serialize_enum<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
-    where
-        T: AsRef<str>,
-        S: ser::Serializer,
-    {
-        serializer.serialize_str(&TemplateData::normalize_enum(value))
+    /// Converts into data for rendering, where enum values are normalized into the identifiers
+    /// used by templates (e.g. `red-green` becomes `REDGREEN`).
+    pub fn to_mustache_data(&self) -> mustache::Data {
+        match self {
+            TemplateData::Enum(value) => {
+                mustache::Data::String(TemplateData::normalize_enum(value))
+            }
+            TemplateData::Float(value) => mustache::Data::String(value.to_string()),
+            TemplateData::Bool(value) => mustache::Data::String(value.to_string()),
+            TemplateData::Map(map) => mustache::Data::Map(
+                map.iter()
+                    .map(|(k, v)| (k.to_owned(), v.to_mustache_data()))
+                    .collect(),
+            ),
+        }
+    }
+
+    /// The value as text, for variables like paths that may happen to parse as a number or
+    /// boolean, or [`None`] for maps.
+    pub fn to_scalar_string(&self) -> Option<String> {
+        match self {
+            TemplateData::Enum(value) => Some(value.to_owned()),
+            TemplateData::Float(value) => Some(value.to_string()),
+            TemplateData::Bool(value) => Some(value.to_string()),
+            TemplateData::Map(_) => None,
+        }
     }
 
-    fn normalize_enum<T: AsRef<str>>(value: &T) -> String {
+    pub fn normalize_enum<T: AsRef<str>>(value: &T) -> String {
         value.as_ref().to_ascii_uppercase().replace(['-', '_'], "")
     }
 }
@@ -175,6 +230,7 @@ impl FromStr for TemplateDataFromCliArg {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         s.parse::<f64>()
             .map(TemplateData::Float)
+            .or_else(|_| s.parse::<bool>().map(TemplateData::Bool))
             .or_else(|_| Ok(TemplateData::Enum(s.to_owned())))
             .map(|value| Self { value })
     }
@@ -196,6 +252,7 @@ mod tests {
                 [data]
                 enum = "red-green"
                 strength = 0.5
+                toggle = true
                 balance = { red = 100.1, green = 253.0, blue = 93.4 }
             "#,
         )
@@ -206,6 +263,7 @@ mod tests {
             TemplateDataMap::from([
                 ("enum".into(), TemplateData::Enum("red-green".into())),
                 ("strength".into(), TemplateData::Float(0.5)),
+                ("toggle".into(), TemplateData::Bool(true)),
                 (
                     "balance".into(),
                     TemplateData::from([
@@ -221,7 +279,7 @@ mod tests {
     #[test]
     fn compile() {
         let template = mustache::compile_str(
-            "({{balance.red}}, {{balance.green}}, {{balance.blue}}) with strength={{strength}} and variant={{variant}}",
+            "({{balance.red}}, {{balance.green}}, {{balance.blue}}) with strength={{strength}}, variant={{variant}}, and toggle={{toggle}}",
         )
         .unwrap();
         let data = TemplateDataMap::from([
@@ -235,15 +293,30 @@ mod tests {
             ),
             ("strength".into(), TemplateData::Float(0.5)),
             ("variant".into(), TemplateData::Enum("red-green".into())),
+            ("toggle".into(), TemplateData::Bool(false)),
         ]);
 
-        let s = template.render_to_string(&data).unwrap();
+        let s = template
+            .render_data_to_string(&data.to_mustache_data())
+            .unwrap();
         assert_eq!(
             s,
-            "(100.1, 253, 93.4) with strength=0.5 and variant=REDGREEN"
+            "(100.1, 253, 93.4) with strength=0.5, variant=REDGREEN, and toggle=false"
         );
     }
 
+    #[test]
+    fn serialize_keeps_enum_verbatim() {
+        let data = TemplateDataMap::from([(
+            "lut".into(),
+            TemplateData::Enum("~/luts/warm-1_b.cube".into()),
+        )]);
+
+        let s = serde_json::to_string(&data).unwrap();
+        assert_eq!(s, r#"{"lut":"~/luts/warm-1_b.cube"}"#);
+        assert_eq!(serde_json::from_str::<TemplateDataMap>(&s).unwrap(), data);
+    }
+
     #[test]
     fn merge_deep() {
         let mut data = TemplateDataMap::from([(
@@ -281,4 +354,36 @@ mod tests {
             data
         );
     }
+
+    #[test]
+    fn get_and_insert_path() {
+        let mut data = TemplateDataMap::from([(
+            String::from("balance"),
+            TemplateData::from([(String::from("red"), TemplateData::Float(1.0))]),
+        )]);
+
+        assert_eq!(
+            data.get_path(&["balance", "red"]),
+            Some(&TemplateData::Float(1.0))
+        );
+        assert_eq!(data.get_path(&["balance", "green"]), None);
+        assert_eq!(data.get_path(&["balance", "red", "foo"]), None);
+
+        data.insert_path(&["balance", "green"], TemplateData::Float(2.0));
+        data.insert_path(&["strength"], TemplateData::Float(0.5));
+
+        assert_eq!(
+            data,
+            TemplateDataMap::from([
+                (
+                    String::from("balance"),
+                    TemplateData::from([
+                        (String::from("red"), TemplateData::Float(1.0)),
+                        (String::from("green"), TemplateData::Float(2.0)),
+                    ]),
+                ),
+                (String::from("strength"), TemplateData::Float(0.5)),
+            ])
+        );
+    }
 }
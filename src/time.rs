@@ -12,6 +12,10 @@ pub fn now() -> chrono::DateTime<chrono::Local> {
 fn mock_now() -> chrono::DateTime<chrono::Local> {
     let mock_time_str = std::env::var("__HYPRSHADE_MOCK_TIME")
         .unwrap_or_else(|err| panic!("reading __HYPRSHADE_MOCK_TIME: {err}"));
+    // An instant like `2026-10-25T01:30:00Z`, or else a time of day today
+    if let Ok(datetime) = chrono::DateTime::parse_from_rfc3339(&mock_time_str) {
+        return datetime.with_timezone(&chrono::Local);
+    }
     let time: chrono::NaiveTime = mock_time_str
         .parse()
         .unwrap_or_else(|err| panic!("parsing '{mock_time_str}': {err}"));
@@ -0,0 +1,104 @@
+//! Shaders turned on for a limited time with `on --for`, and what to revert to afterwards
+
+use std::{fs, io, path::PathBuf};
+
+use chrono::{DateTime, Local};
+use eyre::Context;
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+use crate::{
+    compositor::Hyprland,
+    shader::{Shader, ShaderInstance},
+    timer::Timer,
+    util::{make_runtime_path, write_atomic},
+};
+
+const FILE_NAME: &str = "timed.json";
+
+/// Reverts the shader when its time is up
+static TIMER: Timer = Timer::new("revert");
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimedShader {
+    until: DateTime<Local>,
+    /// File Hyprland had loaded when the shader was turned on, to tell whether it was replaced since
+    instance_path: PathBuf,
+    revert: Revert,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Revert {
+    /// Turns the previous shader back on, or turns the shader off if there was none
+    Previous(Option<ShaderInstance>),
+    /// Turns on whatever the schedule says when the time is up
+    Schedule,
+}
+
+impl TimedShader {
+    pub fn new(until: DateTime<Local>, instance_path: PathBuf, revert: Revert) -> Self {
+        Self {
+            until,
+            instance_path,
+            revert,
+        }
+    }
+
+    pub fn until(&self) -> DateTime<Local> {
+        self.until
+    }
+
+    pub fn revert(&self) -> &Revert {
+        &self.revert
+    }
+
+    pub fn is_expired(&self, now: &DateTime<Local>) -> bool {
+        self.until <= *now
+    }
+
+    /// Returns the timed shader if it is still on, forgetting it if it was replaced.
+    pub fn read() -> eyre::Result<Option<TimedShader>> {
+        let path = make_runtime_path(FILE_NAME)?;
+        let contents = match fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err).wrap_err_with(|| format!("failed to read {path:?}")),
+        };
+        let timed: TimedShader = serde_json::from_str(&contents)
+            .wrap_err_with(|| format!("failed to parse {path:?}"))?;
+
+        let current = Shader::current(&Hyprland)?;
+        if current.is_none_or(|current| current.instance_path() != timed.instance_path) {
+            debug!("Timed shader {:?} is no longer on", timed.instance_path);
+            TimedShader::cancel()?;
+            return Ok(None);
+        }
+        Ok(Some(timed))
+    }
+
+    /// Saves the timed shader, replacing any other, and arranges for it to be reverted when its
+    /// time is up.
+    pub fn start(&self) -> eyre::Result<()> {
+        let path = make_runtime_path(FILE_NAME)?;
+        write_atomic(&path, serde_json::to_string(self)?)
+            .wrap_err_with(|| format!("failed to write {path:?}"))?;
+
+        TIMER
+            .schedule(self.until, &["revert"])
+            .wrap_err("failed to schedule reverting the shader")
+    }
+
+    /// Forgets the timed shader without reverting it, returning whether there was one.
+    pub fn cancel() -> eyre::Result<bool> {
+        let path = make_runtime_path(FILE_NAME)?;
+        match fs::remove_file(&path) {
+            Ok(()) => {
+                TIMER.cancel();
+                Ok(true)
+            }
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(false),
+            Err(err) => Err(err).wrap_err_with(|| format!("failed to remove {path:?}")),
+        }
+    }
+}
@@ -0,0 +1,172 @@
+//! Runs hyprshade again at a later time, e.g. to resume the schedule once `inhibit` expires.
+//!
+//! A transient systemd user timer is used when possible. Otherwise a background process sleeps
+//! until the time comes, and can't be cancelled, so the commands it runs must check for themselves
+//! whether they are still wanted.
+
+use std::{
+    env,
+    ffi::{OsStr, OsString},
+    io,
+    path::Path,
+    process::{Command, Stdio},
+    sync::{PoisonError, RwLock},
+};
+
+use chrono::{DateTime, Local};
+use eyre::Context;
+use tracing::debug;
+
+use crate::{
+    constants::{
+        HYPRLAND_INSTANCE_SIGNATURE_ENV, HYPRSHADE_CONFIG_FILE_ENV, HYPRSHADE_SHADERS_DIR_ENV,
+    },
+    instance,
+    time::now,
+};
+
+const SYSTEMD_RUN: &str = "systemd-run";
+const SYSTEMCTL: &str = "systemctl";
+
+/// Forwarded to the transient unit, which otherwise only gets the environment of the user manager
+const FORWARDED_ENV: &[&str] = &[HYPRSHADE_CONFIG_FILE_ENV, HYPRSHADE_SHADERS_DIR_ENV];
+
+/// Global options that scheduled commands are run with, e.g. `--config`
+static GLOBAL_ARGS: RwLock<Vec<OsString>> = RwLock::new(Vec::new());
+
+/// Runs scheduled commands with the global options `args`, replacing the previous ones.
+pub fn set_global_args(args: Vec<OsString>) {
+    *GLOBAL_ARGS.write().unwrap_or_else(PoisonError::into_inner) = args;
+}
+
+fn global_args() -> Vec<OsString> {
+    GLOBAL_ARGS
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .clone()
+}
+
+#[derive(Debug)]
+pub struct Timer {
+    name: &'static str,
+}
+
+impl Timer {
+    pub const fn new(name: &'static str) -> Self {
+        Self { name }
+    }
+
+    /// Name of the systemd unit, which is separate for each Hyprland instance like the state it
+    /// acts on
+    fn unit(&self) -> String {
+        let mut unit = format!("{}-{}", env!("CARGO_PKG_NAME"), self.name);
+        if let Some(signature) = instance::signature() {
+            unit.push('-');
+            unit.push_str(&escape_unit_name(signature));
+        }
+        unit
+    }
+
+    /// Runs hyprshade with `args` at `at`, replacing whatever this timer was going to run.
+    #[tracing::instrument(level = "debug")]
+    pub fn schedule(&self, at: DateTime<Local>, args: &[&str]) -> eyre::Result<()> {
+        self.cancel();
+
+        let exe = env::current_exe().wrap_err("failed to find hyprshade executable")?;
+        let seconds = (at - now()).num_seconds().max(1);
+
+        match self.systemd_run(seconds, &exe, args) {
+            Ok(()) => return Ok(()),
+            Err(err) => debug!("Falling back to a background process: {err}"),
+        }
+
+        let mut command = Command::new("sh");
+        if let Some(signature) = instance::signature() {
+            command.env(HYPRLAND_INSTANCE_SIGNATURE_ENV, signature);
+        }
+        command
+            .args(["-c", r#"sleep "$0" && exec "$@""#])
+            .arg(seconds.to_string())
+            .arg(&exe)
+            .args(global_args())
+            .args(args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .wrap_err("failed to start background process")?;
+
+        Ok(())
+    }
+
+    /// Stops the systemd timer, if one was started.
+    #[tracing::instrument(level = "debug")]
+    pub fn cancel(&self) {
+        let result = Command::new(SYSTEMCTL)
+            .args(["--user", "stop"])
+            .arg(format!("{}.timer", self.unit()))
+            .stdin(Stdio::null())
+            .output();
+        debug!(?result);
+    }
+
+    fn systemd_run(&self, seconds: i64, exe: &Path, args: &[&str]) -> io::Result<()> {
+        let mut command = Command::new(SYSTEMD_RUN);
+        command
+            .args(["--user", "--collect", "--quiet"])
+            .arg(format!("--unit={}", self.unit()))
+            .arg(format!("--on-active={seconds}s"))
+            .arg("--timer-property=AccuracySec=1s");
+        // The selected instance rather than the one hyprshade is running in
+        if let Some(signature) = instance::signature() {
+            command.arg(format!(
+                "--setenv={HYPRLAND_INSTANCE_SIGNATURE_ENV}={signature}"
+            ));
+        }
+        for key in FORWARDED_ENV {
+            if env::var_os(key).is_some() {
+                command.arg(format!("--setenv={key}"));
+            }
+        }
+        let output = command
+            .arg(exe)
+            .args(global_args())
+            .args(args.iter().map(OsStr::new))
+            .stdin(Stdio::null())
+            .output()?;
+
+        if output.status.success() {
+            Ok(())
+        } else {
+            Err(io::Error::other(
+                String::from_utf8_lossy(&output.stderr).trim().to_owned(),
+            ))
+        }
+    }
+}
+
+/// Escapes characters that aren't allowed in systemd unit names, like `systemd-escape` does.
+fn escape_unit_name(name: &str) -> String {
+    name.bytes()
+        .map(|b| match b {
+            b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b'_' | b'.' | b':' => {
+                char::from(b).to_string()
+            }
+            _ => format!("\\x{b:02x}"),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escapes_unit_name() {
+        assert_eq!(
+            escape_unit_name("a3e7_1720000000_123"),
+            "a3e7_1720000000_123"
+        );
+        assert_eq!(escape_unit_name("nested-1 ä"), r"nested\x2d1\x20\xc3\xa4");
+    }
+}
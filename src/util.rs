@@ -1,12 +1,12 @@
 use std::{
-    ffi::OsStr,
+    ffi::{OsStr, OsString},
     fs, io,
     path::{Path, PathBuf},
 };
 
 use color_eyre::{Section, SectionExt, owo_colors::OwoColorize};
 
-use crate::constants::HYPRSHADE_RUNTIME_DIR;
+use crate::{constants::HYPRSHADE_RUNTIME_DIR, instance, resolver};
 
 pub trait ConfigSection: Section {
     fn config_section(self, path: &Path) -> Self::Return;
@@ -28,9 +28,54 @@ where
     }
 }
 
+pub trait ShaderNameSuggestion: Section {
+    /// Suggests shaders with names similar to one that wasn't found by a [`resolver::Error`] in
+    /// the chain of errors.
+    fn shader_name_suggestion(self) -> Self::Return;
+}
+
+impl ShaderNameSuggestion for eyre::Report {
+    fn shader_name_suggestion(self) -> Self::Return {
+        let names = self
+            .chain()
+            .find_map(|error| error.downcast_ref::<resolver::Error>())
+            .map(resolver::Error::similar_names)
+            .unwrap_or_default();
+        match similar_names_suggestion(&names) {
+            Some(suggestion) => self.suggestion(suggestion),
+            None => self,
+        }
+    }
+}
+
+impl<T, E> ShaderNameSuggestion for eyre::Result<T, E>
+where
+    E: Into<eyre::Report>,
+{
+    fn shader_name_suggestion(self) -> Self::Return {
+        self.map_err(|error| error.into().shader_name_suggestion())
+    }
+}
+
+/// Formats shader names similar to one that wasn't found, or [`None`] if there are none.
+pub fn similar_names_suggestion(names: &[String]) -> Option<String> {
+    match names {
+        [] => None,
+        [name] => Some(format!("Did you mean {name:?}?")),
+        names => Some(format!(
+            "Did you mean one of {}?",
+            names
+                .iter()
+                .map(|name| format!("{name:?}"))
+                .collect::<Vec<_>>()
+                .join(", ")
+        )),
+    }
+}
+
 pub fn make_runtime_path<P: AsRef<Path>>(file_name: P) -> io::Result<PathBuf> {
     fn _make_runtime_path(file_name: &Path) -> io::Result<PathBuf> {
-        let out_path = HYPRSHADE_RUNTIME_DIR.to_owned().join(file_name);
+        let out_path = instance::namespace(&HYPRSHADE_RUNTIME_DIR).join(file_name);
         let parent = out_path
             .parent()
             .ok_or_else(|| io::Error::other(format!("failed to get parent of {out_path:?}")))?;
@@ -41,6 +86,28 @@ pub fn make_runtime_path<P: AsRef<Path>>(file_name: P) -> io::Result<PathBuf> {
     _make_runtime_path(file_name.as_ref())
 }
 
+/// Writes to a temporary file next to `path` first and renames it into place, so that concurrent
+/// readers (including Hyprland) never see a partially written file.
+pub fn write_atomic<P: AsRef<Path>, C: AsRef<[u8]>>(path: P, contents: C) -> io::Result<()> {
+    fn _write_atomic(path: &Path, contents: &[u8]) -> io::Result<()> {
+        let file_name = path
+            .file_name()
+            .ok_or_else(|| io::Error::other(format!("failed to get file name of {path:?}")))?;
+        let mut tmp_file_name = OsString::from(".");
+        tmp_file_name.push(file_name);
+        tmp_file_name.push(format!(".{}.tmp", std::process::id()));
+        let tmp_path = path.with_file_name(tmp_file_name);
+
+        fs::write(&tmp_path, contents)
+            .and_then(|()| fs::rename(&tmp_path, path))
+            .inspect_err(|_| {
+                let _ = fs::remove_file(&tmp_path);
+            })
+    }
+
+    _write_atomic(path.as_ref(), contents.as_ref())
+}
+
 pub trait PathExt {
     /// Extracts the prefix of [`self.file_name`].
     ///
@@ -4,7 +4,8 @@ use std::{
     ffi::OsStr,
     fmt, fs,
     hash::Hash,
-    io,
+    io, iter,
+    os::unix::fs::PermissionsExt,
     path::{Path, PathBuf},
     process::Command,
 };
@@ -13,11 +14,19 @@ use insta_cmd::get_cargo_bin;
 use parking_lot::{Mutex, MutexGuard};
 use tempfile::TempDir;
 
-const DIRS: &[(&str, &str)] = &[("HOME", ""), ("XDG_CONFIG_HOME", ".config")];
+const DIRS: &[(&str, &str)] = &[
+    ("HOME", ""),
+    ("XDG_CONFIG_HOME", ".config"),
+    ("XDG_DATA_HOME", ".local/share"),
+    ("XDG_DATA_DIRS", "usr/share"),
+];
 const CONFIG_DIRS: &[&str] = &["hypr", "hyprshade"];
 
 pub const INSTA_FILTERS: &[(&str, &str)] = &[
-    (r"/run/user/[[:digit:]]+/\S+?", "[RUNTIME_FILE]"),
+    (
+        r#"/\S*?/hyprshade-test-dir/runtime/[^\s"']+"#,
+        "[RUNTIME_FILE]",
+    ),
     (
         r"/.*?/hyprshade-test-dir/home/.config/hyprshade/config.toml",
         "[HYPRSHADE_CONFIG]",
@@ -39,6 +48,7 @@ pub const INSTA_FILTERS: &[(&str, &str)] = &[
 ];
 
 const FIXTURE_SIMPLE: &str = include_str!("./fixtures/simple.glsl");
+const FIXTURE_HYPRCTL: &str = include_str!("./fixtures/hyprctl.sh");
 
 #[track_caller]
 fn bootstrap_home(path: &Path) -> PathBuf {
@@ -55,6 +65,35 @@ fn bootstrap_home(path: &Path) -> PathBuf {
     home
 }
 
+#[track_caller]
+fn bootstrap_runtime_dir(path: &Path) -> PathBuf {
+    let runtime_dir = path.join("hyprshade-test-dir/runtime");
+    fs::create_dir_all(runtime_dir.join("hyprshade")).unwrap();
+    runtime_dir
+}
+
+/// Programs that must not touch the host while testing, which log their arguments instead
+const STUB_PROGRAMS: &[&str] = &["systemd-run", "systemctl"];
+
+#[track_caller]
+fn bootstrap_bin(path: &Path) {
+    let bin = path.join("bin");
+    fs::create_dir_all(&bin).unwrap();
+    for program in STUB_PROGRAMS {
+        let path = bin.join(program);
+        fs::write(
+            &path,
+            format!("#!/bin/sh\necho \"{program} $*\" >> \"$(dirname \"$0\")/calls\"\n"),
+        )
+        .unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+    }
+
+    let path = bin.join("hyprctl");
+    fs::write(&path, FIXTURE_HYPRCTL).unwrap();
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+}
+
 fn get_bin() -> PathBuf {
     get_cargo_bin(env!("CARGO_PKG_NAME"))
 }
@@ -62,10 +101,10 @@ fn get_bin() -> PathBuf {
 static TEST_MUTEX: Mutex<()> = Mutex::new(());
 
 pub struct Space {
-    #[allow(dead_code)]
     tempdir: TempDir,
     working_dir: PathBuf,
     home: PathBuf,
+    runtime_dir: PathBuf,
     time: Option<String>,
 
     /// Used to enforce sequential test execution
@@ -80,11 +119,14 @@ impl Space {
         let tempdir = TempDir::new().unwrap();
         let working_dir = tempdir.path().join("working_dir");
         let home = bootstrap_home(tempdir.path());
+        let runtime_dir = bootstrap_runtime_dir(tempdir.path());
+        bootstrap_bin(tempdir.path());
         fs::create_dir_all(&working_dir).unwrap();
         Self {
             tempdir,
             working_dir,
             home,
+            runtime_dir,
             time: None,
             _lock: lock,
         }
@@ -120,10 +162,16 @@ impl Space {
             cmd.env(key, path);
         }
 
-        const ENV_REMOVE: &[&str] = &["RUST_BACKTRACE", "COLORBT_SHOW_HIDDEN"];
+        const ENV_REMOVE: &[&str] = &[
+            "RUST_BACKTRACE",
+            "COLORBT_SHOW_HIDDEN",
+            "HYPRSHADE_SHADERS_DIR",
+            "HYPRLAND_INSTANCE_SIGNATURE",
+        ];
         for key in ENV_REMOVE {
             cmd.env_remove(key);
         }
+        cmd.env("XDG_RUNTIME_DIR", &self.runtime_dir);
 
         if let Some(time) = &self.time {
             cmd.env("__HYPRSHADE_MOCK_TIME", time);
@@ -131,6 +179,10 @@ impl Space {
             cmd.env_remove("__HYPRSHADE_MOCK_TIME");
         }
 
+        let path = env::var_os("PATH").unwrap_or_default();
+        let path = env::join_paths(iter::once(self.bin_dir()).chain(env::split_paths(&path)));
+        cmd.env("PATH", path.unwrap());
+
         cmd.current_dir(&self.working_dir);
         cmd
     }
@@ -144,13 +196,8 @@ impl Space {
         self.home.as_ref()
     }
 
-    #[track_caller]
     pub fn runtime_dir(&self) -> PathBuf {
-        let runtime_dir = match env::var("XDG_RUNTIME_DIR") {
-            Ok(dir) => dir,
-            Err(err) => panic!("fetching XDG_RUNTIME_DIR: {err}"),
-        };
-        PathBuf::from(runtime_dir).join("hyprshade")
+        self.runtime_dir.join("hyprshade")
     }
 
     #[track_caller]
@@ -228,6 +275,34 @@ impl Space {
             .collect()
     }
 
+    /// Stashes state kept in the runtime directory, like `inhibit.json`.
+    pub fn stash_runtime_state(&self, file_name: &str) -> FileDropGuard {
+        FileDropGuard::stash_and_remove(self.runtime_dir().join(file_name))
+    }
+
+    /// Calls made to the stand-in `systemd-run` and `systemctl`, one per line.
+    #[allow(dead_code)]
+    pub fn systemd_calls(&self) -> String {
+        fs::read_to_string(self.bin_dir().join("calls")).unwrap_or_default()
+    }
+
+    /// Calls made to the stand-in `hyprctl`, one per line.
+    #[allow(dead_code)]
+    pub fn hyprctl_calls(&self) -> String {
+        fs::read_to_string(self.bin_dir().join("hyprctl_calls")).unwrap_or_default()
+    }
+
+    /// Runs the stand-in `hyprctl`, which keeps `decoration:screen_shader` in a file instead of
+    /// talking to Hyprland.
+    #[allow(dead_code)]
+    pub fn hyprctl_cmd(&self) -> Command {
+        self.cmd(self.bin_dir().join("hyprctl"))
+    }
+
+    fn bin_dir(&self) -> PathBuf {
+        self.tempdir.path().join("bin")
+    }
+
     #[track_caller]
     pub fn current_shader(&self) -> Option<String> {
         let output = self.hyprshade_cmd().arg("current").output().unwrap();
@@ -272,6 +347,7 @@ impl From<&&'static str> for ShaderIdentifier {
 pub struct FileDropGuard {
     path: PathBuf,
     stash: Option<String>,
+    remove: bool,
 }
 
 impl FileDropGuard {
@@ -281,7 +357,19 @@ impl FileDropGuard {
         if stash.is_some() {
             fs::remove_file(&path).ok();
         }
-        Self { path, stash }
+        Self {
+            path,
+            stash,
+            remove: false,
+        }
+    }
+
+    /// Like [`FileDropGuard::stash`], but also removes the file on drop if there was nothing to
+    /// restore, for state that would affect later tests.
+    pub fn stash_and_remove<P: AsRef<Path>>(path: P) -> Self {
+        let mut guard = Self::stash(path);
+        guard.remove = true;
+        guard
     }
 }
 
@@ -291,6 +379,8 @@ impl Drop for FileDropGuard {
             fs::write(&self.path, contents).unwrap_or_else(|err| {
                 eprintln!("failed restoring stash to {}: {}", self.path.display(), err);
             });
+        } else if self.remove {
+            fs::remove_file(&self.path).ok();
         }
     }
 }
@@ -0,0 +1,21 @@
+#!/bin/sh
+# Stand-in for `hyprctl`, keeping `decoration:screen_shader` in a file next to this script, with
+# one file for each instance given to `-i`, and logging its arguments to `hyprctl_calls`
+
+state="$(dirname "$0")/screen_shader"
+echo "$*" >> "$(dirname "$0")/hyprctl_calls"
+if [ "$1" = "-i" ]; then
+    state="$state.$2"
+    shift 2
+fi
+[ -f "$state" ] || printf '[[EMPTY]]' > "$state"
+
+if [ "$1" = "-j" ] && [ "$2" = "getoption" ] && [ "$3" = "decoration:screen_shader" ]; then
+    printf '{"option": "%s", "str": "%s", "set": true}\n' "$3" "$(cat "$state")"
+elif [ "$1" = "keyword" ] && [ "$2" = "decoration:screen_shader" ] && [ $# -eq 3 ]; then
+    printf '%s' "$3" > "$state"
+    echo ok
+else
+    echo "unknown request: $*" >&2
+    exit 1
+fi
@@ -1,8 +1,20 @@
 mod common;
+mod test_adjust;
 mod test_auto;
+mod test_completions;
+#[cfg(feature = "dbus")]
+mod test_dbus_service;
+mod test_gc;
+mod test_inhibit;
+mod test_instances;
+mod test_ls;
 mod test_off;
 mod test_on;
+mod test_restore;
+mod test_serve;
+mod test_shader;
 mod test_toggle;
+mod test_which;
 
 #[cfg(not(feature = "_mock_time"))]
 compile_error!(
@@ -0,0 +1,280 @@
+use std::fs;
+
+use crate::common::{CommandExt, Space, hyprshade_cmd_snapshot};
+
+#[test]
+fn float_delta() {
+    let space = Space::new();
+    let _stash = space.stash_runtime_shader("vibrance");
+    space
+        .hyprshade_cmd()
+        .args(["on", "vibrance", "--var", "strength=0.5"])
+        .run();
+
+    hyprshade_cmd_snapshot!(space.hyprshade_cmd().args(["adjust", "strength", "-0.25"]), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+
+    ----- stderr -----
+    ");
+
+    assert!(
+        space
+            .read_runtime_shader("vibrance")
+            .contains("const float Strength = float(0.25);")
+    );
+}
+
+#[test]
+fn float_delta_nested_key_uses_default() {
+    let space = Space::new();
+    let _stash = space.stash_runtime_shader("vibrance");
+    space.hyprshade_cmd().args(["on", "vibrance"]).run();
+
+    space
+        .hyprshade_cmd()
+        .args(["adjust", "balance.red", "+0.5"])
+        .run();
+
+    assert!(
+        space
+            .read_runtime_shader("vibrance")
+            .contains("float(1.5),\n    float(1),\n    float(1)")
+    );
+}
+
+#[test]
+fn float_delta_is_clamped() {
+    let space = Space::new();
+    let _stash = space.stash_runtime_shader("blue-light-filter");
+    space
+        .hyprshade_cmd()
+        .args(["on", "blue-light-filter"])
+        .run();
+
+    space
+        .hyprshade_cmd()
+        .args(["adjust", "temperature", "-100000"])
+        .run();
+
+    assert!(
+        space
+            .read_runtime_shader("blue-light-filter")
+            .contains("const float Temperature = float(1000);")
+    );
+}
+
+#[test]
+fn enum_next_and_prev() {
+    let space = Space::new();
+    let _stash = space.stash_runtime_shader("grayscale");
+    space.hyprshade_cmd().args(["on", "grayscale"]).run();
+
+    space
+        .hyprshade_cmd()
+        .args(["adjust", "type", "--next"])
+        .run();
+    assert!(
+        space
+            .read_runtime_shader("grayscale")
+            .contains("const int Type = LIGHTNESS;")
+    );
+
+    space
+        .hyprshade_cmd()
+        .args(["adjust", "type", "--prev"])
+        .run();
+    space
+        .hyprshade_cmd()
+        .args(["adjust", "type", "--prev"])
+        .run();
+    assert!(
+        space
+            .read_runtime_shader("grayscale")
+            .contains("const int Type = AVERAGE;")
+    );
+}
+
+#[test]
+fn bool_toggle() {
+    let space = Space::new();
+    let _stash = space.stash_runtime_shader("blue-light-filter");
+    space
+        .hyprshade_cmd()
+        .args(["on", "blue-light-filter"])
+        .run();
+
+    space
+        .hyprshade_cmd()
+        .args(["adjust", "preserve_luminance", "--next"])
+        .run();
+    assert!(
+        space
+            .read_runtime_shader("blue-light-filter")
+            .contains("const bool PreserveLuminance = true;")
+    );
+
+    space
+        .hyprshade_cmd()
+        .args(["adjust", "preserve_luminance", "--prev"])
+        .run();
+    assert!(
+        space
+            .read_runtime_shader("blue-light-filter")
+            .contains("const bool PreserveLuminance = false;")
+    );
+}
+
+#[test]
+fn uses_sidecar_metadata() {
+    let space = Space::new();
+    let _stash = space.stash_runtime_shader("warm");
+    let shader = space.working_dir().join("warm.glsl.mustache");
+    fs::write(
+        &shader,
+        "const float Strength = float({{strength}});\nconst int Mode = {{mode}};\n",
+    )
+    .unwrap();
+    fs::write(
+        space.working_dir().join("warm.toml"),
+        r#"
+            [variables.strength]
+            type = "float"
+            min = 0
+            max = 1
+            default = 0.5
+
+            [variables.mode]
+            type = "enum"
+            variants = ["soft", "hard"]
+            default = "soft"
+        "#,
+    )
+    .unwrap();
+    space.hyprshade_cmd().arg("on").arg(&shader).run();
+
+    space
+        .hyprshade_cmd()
+        .args(["adjust", "strength", "+1"])
+        .run();
+    space
+        .hyprshade_cmd()
+        .args(["adjust", "mode", "--next"])
+        .run();
+    let rendered = space.read_runtime_shader("warm");
+    assert!(
+        rendered.contains("const float Strength = float(1);"),
+        "{rendered}"
+    );
+    assert!(rendered.contains("const int Mode = HARD;"), "{rendered}");
+}
+
+mod error {
+
+    use crate::common::{CommandExt, Space, hyprshade_cmd_snapshot};
+
+    #[test]
+    fn fails_without_change() {
+        let space = Space::new();
+        hyprshade_cmd_snapshot!(space.hyprshade_cmd().args(["adjust", "strength"]), @"
+        success: false
+        exit_code: 2
+        ----- stdout -----
+
+        ----- stderr -----
+        error: the following required arguments were not provided:
+          <DELTA|--next|--prev>
+
+        Usage: hyprshade adjust <DELTA|--next|--prev> <KEY>
+
+        For more information, try '--help'.
+        ");
+    }
+
+    #[test]
+    fn fails_without_active_shader() {
+        let space = Space::new();
+        space.hyprshade_cmd().arg("off").run();
+
+        hyprshade_cmd_snapshot!(space.hyprshade_cmd().args(["adjust", "strength", "0.1"]), @"
+        success: false
+        exit_code: 1
+        ----- stdout -----
+
+        ----- stderr -----
+        Error: 
+           0: [91mno shader is currently active[0m
+
+        Location:
+           [LOCATION]
+        ");
+    }
+
+    #[test]
+    fn fails_with_unknown_variable() {
+        let space = Space::new();
+        let _stash = space.stash_runtime_shader("vibrance");
+        space.hyprshade_cmd().args(["on", "vibrance"]).run();
+
+        hyprshade_cmd_snapshot!(space.hyprshade_cmd().args(["adjust", "invalid", "0.1"]), @r#"
+        success: false
+        exit_code: 1
+        ----- stdout -----
+
+        ----- stderr -----
+        Error: 
+           0: [91mshader "vibrance" has no variable "invalid"[0m
+
+        Location:
+           [LOCATION]
+        "#);
+    }
+
+    #[test]
+    fn fails_cycling_float_variable() {
+        let space = Space::new();
+        let _stash = space.stash_runtime_shader("vibrance");
+        space.hyprshade_cmd().args(["on", "vibrance"]).run();
+
+        hyprshade_cmd_snapshot!(space.hyprshade_cmd().args(["adjust", "strength", "--next"]), @r#"
+        success: false
+        exit_code: 1
+        ----- stdout -----
+
+        ----- stderr -----
+        Error: 
+           0: [91mvariable "strength" of shader "vibrance" has no known variants[0m
+
+        Location:
+           [LOCATION]
+
+        [96mNote[0m: --next and --prev only apply to enum variables listed in the shader's metadata
+        "#);
+    }
+
+    #[test]
+    fn fails_cycling_invalid_variant() {
+        let space = Space::new();
+        let _stash = space.stash_runtime_shader("grayscale");
+        space
+            .hyprshade_cmd()
+            .args(["on", "grayscale", "--var", "type=invalid"])
+            .run();
+
+        hyprshade_cmd_snapshot!(space.hyprshade_cmd().args(["adjust", "type", "--next"]), @r#"
+        success: false
+        exit_code: 1
+        ----- stdout -----
+
+        ----- stderr -----
+        Error: 
+           0: [91mvariable "type" of shader "grayscale" has invalid value "invalid"[0m
+
+        Location:
+           [LOCATION]
+
+        [96mNote[0m: Valid values are luminosity, lightness, average
+        "#);
+    }
+}
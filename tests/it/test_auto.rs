@@ -1,6 +1,111 @@
+use crate::common::{CommandExt, Space};
+
+const SCHEDULE: &str = r#"
+    [[shader]]
+    name = "vibrance"
+    default = true
+
+    [[shader]]
+    name = "grayscale"
+    start_time = 02:30:00
+    end_time = 05:00:00
+"#;
+
+/// Runs `auto` at the instant `time` on a system whose time zone is UTC, and returns the shader.
+#[track_caller]
+fn auto_at(space: &mut Space, time: &str) -> Option<String> {
+    space.with_time(time);
+    space.hyprshade_cmd().arg("auto").env("TZ", "UTC").run();
+    space.current_shader()
+}
+
+#[test]
+fn timezone() {
+    let mut space = Space::new();
+    let _shaders = space.stash_runtime_shaders(["vibrance", "grayscale"]);
+
+    space.with_config(SCHEDULE);
+    assert_eq!(
+        auto_at(&mut space, "2026-05-31T18:00:00Z").as_deref(),
+        Some("vibrance")
+    );
+
+    space.with_config(&format!("timezone = \"Asia/Tokyo\"\n{SCHEDULE}"));
+    // 03:00 in Tokyo
+    assert_eq!(
+        auto_at(&mut space, "2026-05-31T18:00:00Z").as_deref(),
+        Some("grayscale")
+    );
+}
+
+#[test]
+fn clocks_go_forward() {
+    let mut space = Space::new();
+    let _shaders = space.stash_runtime_shaders(["vibrance", "grayscale"]);
+    space.with_config(&format!("timezone = \"Europe/Berlin\"\n{SCHEDULE}"));
+
+    // 01:59 CET, then 03:00 CEST; 02:30 never happens
+    assert_eq!(
+        auto_at(&mut space, "2026-03-29T00:59:00Z").as_deref(),
+        Some("vibrance")
+    );
+    assert_eq!(
+        auto_at(&mut space, "2026-03-29T01:00:00Z").as_deref(),
+        Some("grayscale")
+    );
+}
+
+#[test]
+fn clocks_go_back() {
+    let mut space = Space::new();
+    let _shaders = space.stash_runtime_shaders(["vibrance", "grayscale"]);
+    space.with_config(&format!("timezone = \"Europe/Berlin\"\n{SCHEDULE}"));
+
+    // 02:15 CEST, then 02:45 CEST
+    assert_eq!(
+        auto_at(&mut space, "2026-10-25T00:15:00Z").as_deref(),
+        Some("vibrance")
+    );
+    assert_eq!(
+        auto_at(&mut space, "2026-10-25T00:45:00Z").as_deref(),
+        Some("grayscale")
+    );
+    // 02:15 CET, when grayscale already started
+    assert_eq!(
+        auto_at(&mut space, "2026-10-25T01:15:00Z").as_deref(),
+        Some("grayscale")
+    );
+}
+
 mod error {
     use crate::common::{Space, hyprshade_cmd_snapshot};
 
+    #[test]
+    fn fails_with_unknown_timezone() {
+        let mut space = Space::new();
+        space
+            .with_any_time()
+            .with_config(r#"timezone = "Europe/Atlantis""#);
+        hyprshade_cmd_snapshot!(space.hyprshade_cmd().arg("auto"), @r#"
+        success: false
+        exit_code: 1
+        ----- stdout -----
+
+        ----- stderr -----
+        Error: 
+           0: [91mparsing config file at [HYPRSHADE_CONFIG][0m
+           1: [91mTOML parse error at line 1, column 12
+           1:   |
+           1: 1 | timezone = "Europe/Atlantis"
+           1:   |            ^^^^^^^^^^^^^^^^^
+           1: failed to parse timezone: 'Europe/Atlantis'
+           1: [0m
+
+        Location:
+           [LOCATION]
+        "#);
+    }
+
     #[test]
     fn fails_without_config() {
         let mut space = Space::new();
@@ -52,4 +157,36 @@ mod error {
         [96mSuggestion[0m: For more information, see [URL]
         "###);
     }
+
+    #[test]
+    fn suggests_similar_names() {
+        let mut space = Space::new();
+        space.with_any_time().with_config(
+            r#"
+                [[shader]]
+                name = "vibrant"
+                default = true
+            "#,
+        );
+        hyprshade_cmd_snapshot!(space.hyprshade_cmd().arg("auto"), @r#"
+        success: false
+        exit_code: 1
+        ----- stdout -----
+
+        ----- stderr -----
+        Error: 
+           0: [91mresolving shader in config[0m
+           1: [91mshader named "vibrant" not found[0m
+
+        Location:
+           [LOCATION]
+
+        Configuration:
+           [33m[HYPRSHADE_CONFIG][39m
+
+        [96mSuggestion[0m: Did you mean "vibrance"?
+        [96mSuggestion[0m: Change the shader name in your configuration, or make sure a shader by that name exists
+        [96mSuggestion[0m: For more information, see [URL]
+        "#);
+    }
 }
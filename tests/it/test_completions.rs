@@ -0,0 +1,89 @@
+use std::fs;
+
+use crate::common::{Space, hyprshade_cmd_snapshot};
+
+fn complete<'a>(space: &Space, args: impl IntoIterator<Item = &'a str>) -> std::process::Command {
+    let mut cmd = space.hyprshade_cmd();
+    cmd.env("COMPLETE", "fish")
+        .args(["--", "hyprshade"])
+        .args(args);
+    cmd
+}
+
+#[test]
+fn shader_names() {
+    let space = Space::new();
+    let shader_dir = space.home().join(".config/hypr/shaders");
+    fs::create_dir_all(&shader_dir).unwrap();
+    fs::write(shader_dir.join("vivid.glsl"), "void main() {}\n").unwrap();
+
+    hyprshade_cmd_snapshot!(complete(&space, ["on", "vi"]), @"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    vibrance	builtin
+    vivid	[HYPRSHADE_HOME]/.config/hypr/shaders/vivid.glsl
+
+    ----- stderr -----
+    ");
+}
+
+#[test]
+fn var_keys_from_sidecar() {
+    let space = Space::new();
+    let shader_dir = space.home().join(".config/hypr/shaders");
+    fs::create_dir_all(&shader_dir).unwrap();
+    fs::write(shader_dir.join("warm.glsl.mustache"), "void main() {}\n").unwrap();
+    fs::write(
+        shader_dir.join("warm.toml"),
+        r#"
+            [variables.tone]
+            type = "enum"
+            description = "Tone of the tint"
+            variants = ["amber", "rose"]
+            default = "amber"
+
+            [variables.balance.red]
+            type = "float"
+            default = 1.0
+        "#,
+    )
+    .unwrap();
+
+    hyprshade_cmd_snapshot!(complete(&space, ["on", "warm", "--var", ""]), @"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    balance.red=
+    tone=	Tone of the tint
+
+    ----- stderr -----
+    ");
+    hyprshade_cmd_snapshot!(complete(&space, ["on", "warm", "--var", "tone="]), @"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    tone=amber
+    tone=rose
+
+    ----- stderr -----
+    ");
+}
+
+#[test]
+fn var_fallback_values() {
+    let space = Space::new();
+
+    hyprshade_cmd_snapshot!(
+        complete(&space, ["toggle", "--fallback", "color-filter", "--var-fallback", "mode="]),
+        @"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    mode=correct
+    mode=simulate
+
+    ----- stderr -----
+    "
+    );
+}
@@ -0,0 +1,223 @@
+use std::{
+    collections::HashMap,
+    io::{BufRead, BufReader},
+    process::{Child, Command, Stdio},
+    sync::mpsc,
+    thread,
+    time::{Duration, Instant},
+};
+
+use zbus::blocking::{Connection, Proxy, connection, fdo::DBusProxy};
+
+use crate::common::Space;
+
+const BUS_NAME: &str = "io.github.loqusion.Hyprshade";
+const OBJECT_PATH: &str = "/io/github/loqusion/Hyprshade";
+const INTERFACE: &str = "io.github.loqusion.Hyprshade1";
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Private session bus and `hyprshade dbus-service` running on it, both stopped on drop
+struct Service {
+    daemon: Child,
+    service: Child,
+    connection: Connection,
+}
+
+impl Service {
+    #[track_caller]
+    fn start(space: &Space) -> Self {
+        let bus_path = space.working_dir().join("bus");
+        let mut daemon = Command::new("dbus-daemon")
+            .arg("--session")
+            .arg(format!("--address=unix:path={}", bus_path.display()))
+            .args(["--nofork", "--print-address"])
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .expect("failed to start dbus-daemon");
+        let mut address = String::new();
+        BufReader::new(daemon.stdout.take().unwrap())
+            .read_line(&mut address)
+            .unwrap();
+        let address = address.trim().to_owned();
+
+        let service = space
+            .hyprshade_cmd()
+            .arg("dbus-service")
+            .env("DBUS_SESSION_BUS_ADDRESS", &address)
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .unwrap();
+
+        let connection = connection::Builder::address(address.as_str())
+            .unwrap()
+            .build()
+            .unwrap();
+        let dbus = DBusProxy::new(&connection).unwrap();
+        let start = Instant::now();
+        while !dbus.name_has_owner(BUS_NAME.try_into().unwrap()).unwrap() {
+            assert!(start.elapsed() < TIMEOUT, "{BUS_NAME} was never owned");
+            thread::sleep(Duration::from_millis(20));
+        }
+
+        Self {
+            daemon,
+            service,
+            connection,
+        }
+    }
+
+    fn proxy(&self) -> Proxy<'_> {
+        Proxy::new(&self.connection, BUS_NAME, OBJECT_PATH, INTERFACE).unwrap()
+    }
+
+    fn on(&self, shader: &str, vars: &[(&str, &str)]) -> zbus::Result<()> {
+        let vars: HashMap<&str, &str> = vars.iter().copied().collect();
+        self.proxy().call("On", &(shader, vars))
+    }
+
+    fn toggle(&self, shader: &str) -> zbus::Result<()> {
+        self.proxy()
+            .call("Toggle", &(shader, HashMap::<&str, &str>::new()))
+    }
+
+    fn off(&self) -> zbus::Result<()> {
+        self.proxy().call("Off", &())
+    }
+
+    fn current(&self) -> String {
+        self.proxy().call("Current", &()).unwrap()
+    }
+
+    fn schedule(&self) -> String {
+        self.proxy().call("Schedule", &()).unwrap()
+    }
+}
+
+impl Drop for Service {
+    fn drop(&mut self) {
+        for child in [&mut self.service, &mut self.daemon] {
+            child.kill().ok();
+            child.wait().ok();
+        }
+    }
+}
+
+#[test]
+fn on_and_off() {
+    let space = Space::new();
+    let service = Service::start(&space);
+
+    service.on("vibrance", &[("balance.red", "0.5")]).unwrap();
+    assert_eq!(service.current(), "vibrance");
+    assert_eq!(space.current_shader().as_deref(), Some("vibrance"));
+    assert!(space.read_runtime_shader("vibrance").contains("0.5"));
+
+    service.off().unwrap();
+    assert_eq!(service.current(), "");
+    assert_eq!(space.current_shader(), None);
+}
+
+#[test]
+fn toggle() {
+    let mut space = Space::new();
+    space.with_any_time();
+    let service = Service::start(&space);
+
+    service.toggle("grayscale").unwrap();
+    assert_eq!(service.current(), "grayscale");
+    service.toggle("grayscale").unwrap();
+    assert_eq!(service.current(), "");
+}
+
+#[test]
+fn emits_shader_changed() {
+    let space = Space::new();
+    let service = Service::start(&space);
+
+    let (tx, rx) = mpsc::channel();
+    let signals = service.proxy().receive_signal("ShaderChanged").unwrap();
+    thread::spawn(move || {
+        for message in signals {
+            let shader: String = message.body().deserialize().unwrap();
+            if tx.send(shader).is_err() {
+                break;
+            }
+        }
+    });
+
+    service.on("vibrance", &[]).unwrap();
+    assert_eq!(rx.recv_timeout(TIMEOUT).unwrap(), "vibrance");
+    // Only the variables change
+    service.on("vibrance", &[("balance.red", "0.5")]).unwrap();
+    assert_eq!(rx.recv_timeout(TIMEOUT).unwrap(), "vibrance");
+    service.off().unwrap();
+    assert_eq!(rx.recv_timeout(TIMEOUT).unwrap(), "");
+}
+
+#[test]
+fn schedule() {
+    let mut space = Space::new();
+    space.with_time("12:00:00").with_config(
+        r#"
+            [[shader]]
+            name = "grayscale"
+            start_time = 10:00:00
+            end_time = 14:00:00
+        "#,
+    );
+    let service = Service::start(&space);
+
+    assert_eq!(service.schedule(), "grayscale");
+    assert_eq!(service.current(), "");
+}
+
+#[test]
+fn picks_up_config_changes() {
+    let mut space = Space::new();
+    space.with_time("12:00:00");
+    let service = Service::start(&space);
+    assert_eq!(service.schedule(), "");
+
+    space.with_config(
+        r#"
+            [[shader]]
+            name = "grayscale"
+            default = true
+        "#,
+    );
+    assert_eq!(service.schedule(), "grayscale");
+
+    space.with_config(
+        r#"
+            [[shader]]
+            name = "vibrance"
+            default = true
+        "#,
+    );
+    service.toggle("").unwrap();
+    assert_eq!(space.current_shader().as_deref(), Some("vibrance"));
+}
+
+#[test]
+fn reports_errors() {
+    let space = Space::new();
+    let service = Service::start(&space);
+
+    let err = service.on("invalid", &[]).unwrap_err();
+    assert!(
+        err.to_string()
+            .contains(r#"shader named "invalid" not found"#),
+        "{err}"
+    );
+    assert_eq!(service.current(), "");
+
+    // Not taken for an option
+    let err = service.on("--help", &[]).unwrap_err();
+    assert!(
+        err.to_string()
+            .contains(r#"shader named "--help" not found"#),
+        "{err}"
+    );
+}
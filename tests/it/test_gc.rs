@@ -0,0 +1,120 @@
+use std::{
+    fs::{self, File},
+    path::Path,
+    time::SystemTime,
+};
+
+use crate::common::{CommandExt, Space};
+
+fn backdate(path: &Path) {
+    File::options()
+        .write(true)
+        .open(path)
+        .unwrap()
+        .set_modified(SystemTime::UNIX_EPOCH)
+        .unwrap();
+}
+
+fn write_instance(space: &Space, name: &str) {
+    let instance_path = space.runtime_dir().join(format!("{name}.glsl"));
+    fs::write(
+        space.runtime_dir().join(format!("{name}.json")),
+        format!(
+            r#"{{"source":{{"Builtin":"vibrance"}},"instance_path":{instance_path:?},"data":{{}}}}"#
+        ),
+    )
+    .unwrap();
+}
+
+#[test]
+fn removes_unused_instances() {
+    let space = Space::new();
+    let _vibrance = space.stash_runtime_shader("vibrance");
+    let _stashes = [
+        "gc-old.glsl",
+        "gc-old.json",
+        "gc-orphan.json",
+        "gc-fresh.glsl",
+        "gc-fresh.json",
+        ".gc-tmp.glsl.1.tmp",
+    ]
+    .map(|file_name| space.stash_runtime_state(file_name));
+    let runtime_dir = space.runtime_dir();
+
+    space.hyprshade_cmd().args(["on", "vibrance"]).run();
+    backdate(&runtime_dir.join("vibrance.glsl"));
+    backdate(&runtime_dir.join("vibrance.json"));
+
+    for name in ["gc-old", "gc-fresh"] {
+        fs::write(runtime_dir.join(format!("{name}.glsl")), "").unwrap();
+        write_instance(&space, name);
+    }
+    backdate(&runtime_dir.join("gc-old.glsl"));
+    backdate(&runtime_dir.join("gc-old.json"));
+    write_instance(&space, "gc-orphan");
+    fs::write(runtime_dir.join(".gc-tmp.glsl.1.tmp"), "").unwrap();
+    backdate(&runtime_dir.join(".gc-tmp.glsl.1.tmp"));
+
+    let output = space.hyprshade_cmd().args(["gc", "--dry-run"]).run();
+    let mut garbage: Vec<_> = String::from_utf8(output.stdout)
+        .unwrap()
+        .lines()
+        .map(|line| Path::new(line).file_name().unwrap().to_owned())
+        .collect();
+    garbage.sort();
+    assert_eq!(
+        garbage,
+        [
+            ".gc-tmp.glsl.1.tmp",
+            "gc-old.glsl",
+            "gc-old.json",
+            "gc-orphan.json"
+        ]
+    );
+    assert!(runtime_dir.join("gc-old.glsl").exists());
+
+    space.hyprshade_cmd().arg("gc").run();
+    for file_name in garbage {
+        assert!(!runtime_dir.join(file_name).exists());
+    }
+    for file_name in [
+        "vibrance.glsl",
+        "vibrance.json",
+        "gc-fresh.glsl",
+        "gc-fresh.json",
+    ] {
+        assert!(runtime_dir.join(file_name).exists(), "{file_name}");
+    }
+}
+
+#[test]
+fn runs_when_shader_changes() {
+    let space = Space::new();
+    let _stashes =
+        ["gc-old.glsl", "gc-old.json"].map(|file_name| space.stash_runtime_state(file_name));
+    let runtime_dir = space.runtime_dir();
+
+    fs::write(runtime_dir.join("gc-old.glsl"), "").unwrap();
+    write_instance(&space, "gc-old");
+    backdate(&runtime_dir.join("gc-old.json"));
+
+    space.hyprshade_cmd().arg("off").run();
+    assert!(!runtime_dir.join("gc-old.glsl").exists());
+    assert!(!runtime_dir.join("gc-old.json").exists());
+}
+
+#[test]
+fn reuses_shader_loaded_when_changing() {
+    let space = Space::new();
+    let _vibrance = space.stash_runtime_shader("vibrance");
+
+    space.hyprshade_cmd().args(["on", "vibrance"]).run();
+    space.hyprshade_cmd().arg("off").run();
+
+    let calls = space.hyprctl_calls();
+    let gets = calls
+        .lines()
+        .filter(|call| call.contains("getoption"))
+        .count();
+    assert_eq!(gets, 1, "{calls}");
+}
@@ -0,0 +1,168 @@
+use std::fs;
+
+use crate::common::{CommandExt, Space, hyprshade_cmd_snapshot};
+
+const CONFIG: &str = r#"
+    [[shader]]
+    name = "vibrance"
+    default = true
+"#;
+
+#[test]
+fn until_time_of_day() {
+    let mut space = Space::new();
+    space.with_time("18:00:00");
+    let _inhibit = space.stash_runtime_state("inhibit.json");
+
+    space
+        .hyprshade_cmd()
+        .args(["inhibit", "--keep-shader", "--until", "17:00"])
+        .run();
+
+    // 17:00 has passed, so it's tomorrow
+    let state = fs::read_to_string(space.runtime_dir().join("inhibit.json")).unwrap();
+    assert!(state.contains("T17:00:00"), "{state}");
+    let calls = space.systemd_calls();
+    assert!(calls.contains("--on-active=82800s"), "{calls}");
+    assert!(calls.trim_end().ends_with(" auto"), "{calls}");
+}
+
+#[test]
+fn until_time_of_day_in_timezone() {
+    let mut space = Space::new();
+    // 12:00 in Tokyo
+    space
+        .with_time("2026-05-31T03:00:00Z")
+        .with_config(&format!("timezone = \"Asia/Tokyo\"\n{CONFIG}"));
+    let _inhibit = space.stash_runtime_state("inhibit.json");
+
+    space
+        .hyprshade_cmd()
+        .args(["inhibit", "--keep-shader", "--until", "17:00"])
+        .run();
+
+    let calls = space.systemd_calls();
+    assert!(calls.contains("--on-active=18000s"), "{calls}");
+}
+
+#[test]
+fn without_end_cancels_timer() {
+    let mut space = Space::new();
+    space.with_any_time();
+    let _inhibit = space.stash_runtime_state("inhibit.json");
+
+    space
+        .hyprshade_cmd()
+        .args(["inhibit", "--keep-shader"])
+        .run();
+
+    assert_eq!(
+        space.systemd_calls(),
+        "systemctl --user stop hyprshade-inhibit.timer\n"
+    );
+}
+
+#[test]
+fn forwards_global_options() {
+    let mut space = Space::new();
+    space.with_any_time();
+    let _inhibit = space.stash_runtime_state("inhibit.json");
+    fs::write(space.working_dir().join("other.toml"), CONFIG).unwrap();
+
+    space
+        .hyprshade_cmd()
+        .args([
+            "--config",
+            "other.toml",
+            "--no-cache",
+            "inhibit",
+            "--for",
+            "1h",
+        ])
+        .run();
+
+    let calls = space.systemd_calls();
+    let config = space.working_dir().join("other.toml");
+    assert!(
+        calls.contains(&format!(" --config {} --no-cache auto", config.display())),
+        "{calls}"
+    );
+}
+
+#[test]
+fn for_conflicts_with_until() {
+    let space = Space::new();
+
+    hyprshade_cmd_snapshot!(space.hyprshade_cmd().args(["inhibit", "--for", "1h", "--until", "17:00"]), @"
+    success: false
+    exit_code: 2
+    ----- stdout -----
+
+    ----- stderr -----
+    error: the argument '--for <DURATION>' cannot be used with '--until <TIME>'
+
+    Usage: hyprshade inhibit --for <DURATION>
+
+    For more information, try '--help'.
+    ");
+}
+
+#[test]
+fn pauses_auto_until_uninhibit() {
+    let mut space = Space::new();
+    space.with_time("12:00:00").with_config(CONFIG);
+    let _inhibit = space.stash_runtime_state("inhibit.json");
+    let _shader = space.stash_runtime_shader("vibrance");
+
+    space.hyprshade_cmd().arg("auto").run();
+    assert_eq!(space.current_shader().as_deref(), Some("vibrance"));
+
+    space
+        .hyprshade_cmd()
+        .args(["inhibit", "--for", "45m"])
+        .run();
+    assert_eq!(space.current_shader(), None);
+    assert!(space.systemd_calls().contains("--on-active=2700s"));
+
+    space.hyprshade_cmd().arg("auto").run();
+    assert_eq!(space.current_shader(), None);
+
+    let output = space
+        .hyprshade_cmd()
+        .args(["current", "--long"])
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.starts_with("inhibited: until "), "{stdout}");
+    assert!(stdout.ends_with(" 12:45:00\n"), "{stdout}");
+
+    for args in [&["current"][..], &["ls"]] {
+        let output = space.hyprshade_cmd().args(args).output().unwrap();
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        assert!(
+            stderr.contains("The schedule is inhibited until ") && stderr.contains(" 12:45:00"),
+            "{args:?}: {stderr}"
+        );
+    }
+
+    space.hyprshade_cmd().arg("uninhibit").run();
+    assert_eq!(space.current_shader().as_deref(), Some("vibrance"));
+}
+
+#[test]
+fn expires() {
+    let mut space = Space::new();
+    space.with_time("12:00:00").with_config(CONFIG);
+    let _inhibit = space.stash_runtime_state("inhibit.json");
+    let _shader = space.stash_runtime_shader("vibrance");
+
+    space
+        .hyprshade_cmd()
+        .args(["inhibit", "--for", "10m"])
+        .run();
+    space.with_time("12:10:00");
+    space.hyprshade_cmd().arg("auto").run();
+
+    assert_eq!(space.current_shader().as_deref(), Some("vibrance"));
+    assert!(!space.runtime_dir().join("inhibit.json").exists());
+}
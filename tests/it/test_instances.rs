@@ -0,0 +1,108 @@
+use std::{fs, os::unix::net::UnixListener};
+
+use crate::common::{CommandExt, Space};
+
+fn current_shader(space: &Space, instance: &str) -> String {
+    let output = space
+        .hyprshade_cmd()
+        .args(["--instance", instance, "current"])
+        .run();
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn lists_running_instances() {
+    let space = Space::new();
+    let hypr_dir = space.runtime_dir().parent().unwrap().join("hypr");
+    let _listeners = ["b_2_2", "a_1_1"].map(|signature| {
+        fs::create_dir_all(hypr_dir.join(signature)).unwrap();
+        UnixListener::bind(hypr_dir.join(signature).join(".socket.sock")).unwrap()
+    });
+    // Left behind by an instance that crashed
+    fs::create_dir_all(hypr_dir.join("c_3_3")).unwrap();
+    drop(UnixListener::bind(hypr_dir.join("c_3_3/.socket.sock")).unwrap());
+
+    let output = space.hyprshade_cmd().arg("instances").run();
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "a_1_1\nb_2_2\n");
+}
+
+#[test]
+fn keeps_instances_apart() {
+    let mut space = Space::new();
+    space.with_any_time();
+
+    space
+        .hyprshade_cmd()
+        .args(["--instance", "a_1_1", "on", "vibrance"])
+        .run();
+    space
+        .hyprshade_cmd()
+        .args(["on", "grayscale", "--instance", "b_2_2"])
+        .run();
+
+    assert_eq!(current_shader(&space, "a_1_1"), "vibrance\n");
+    assert_eq!(current_shader(&space, "b_2_2"), "grayscale\n");
+    assert_eq!(space.current_shader(), None);
+    assert!(space.runtime_dir().join("a_1_1/vibrance.glsl").is_file());
+    assert!(space.runtime_dir().join("b_2_2/grayscale.glsl").is_file());
+    assert!(!space.runtime_dir().join("vibrance.glsl").exists());
+
+    space
+        .hyprshade_cmd()
+        .args(["--instance", "a_1_1", "off"])
+        .run();
+    assert_eq!(current_shader(&space, "a_1_1"), "");
+    assert_eq!(current_shader(&space, "b_2_2"), "grayscale\n");
+}
+
+#[test]
+fn keeps_timers_apart() {
+    let mut space = Space::new();
+    space.with_any_time();
+
+    space
+        .hyprshade_cmd()
+        .args(["--instance", "b_2_2", "inhibit", "--for", "1h"])
+        .run();
+
+    let calls = space.systemd_calls();
+    assert!(
+        calls.contains("systemctl --user stop hyprshade-inhibit-b_2_2.timer\n"),
+        "{calls}"
+    );
+    assert!(calls.contains("--unit=hyprshade-inhibit-b_2_2 "), "{calls}");
+}
+
+#[test]
+fn defaults_to_running_instance() {
+    let mut space = Space::new();
+    space.with_any_time();
+
+    space
+        .hyprshade_cmd()
+        .args(["on", "vibrance"])
+        .env("HYPRLAND_INSTANCE_SIGNATURE", "a_1_1")
+        .run();
+    assert_eq!(current_shader(&space, "a_1_1"), "vibrance\n");
+    assert!(space.runtime_dir().join("a_1_1/vibrance.glsl").is_file());
+
+    // --instance takes precedence
+    let output = space
+        .hyprshade_cmd()
+        .args(["current", "--instance", "b_2_2"])
+        .env("HYPRLAND_INSTANCE_SIGNATURE", "a_1_1")
+        .run();
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "");
+}
+
+#[test]
+fn rejects_paths() {
+    let space = Space::new();
+
+    let output = space
+        .hyprshade_cmd()
+        .args(["--instance", "../a_1_1", "current"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+}
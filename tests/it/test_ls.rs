@@ -0,0 +1,82 @@
+use std::fs;
+
+use crate::common::{Space, hyprshade_cmd_snapshot};
+
+const SHADER: &str = "void main() {}\n";
+
+fn write_shaders(space: &Space, paths: &[&str]) {
+    for path in paths {
+        let path = space.home().join(".config").join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, SHADER).unwrap();
+    }
+}
+
+#[test]
+fn long() {
+    let space = Space::new();
+    write_shaders(
+        &space,
+        &[
+            "hypr/shaders/sub/warm.glsl",
+            "hypr/shaders/vibrance.glsl",
+            "hypr/shaders/vibrance.toml",
+            "hyprshade/shaders/warm.glsl",
+        ],
+    );
+
+    hyprshade_cmd_snapshot!(space.hyprshade_cmd().args(["ls", "--long"]), @"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    blue-light-filter  (builtin)
+    color-adjust       (builtin)
+    color-filter       (builtin)
+    grayscale          (builtin)
+    invert-colors      (builtin)
+    lut                (builtin)
+    vibrance           [HYPRSHADE_HOME]/.config/hypr/shaders/vibrance.glsl
+    warm               [HYPRSHADE_HOME]/.config/hypr/shaders/sub/warm.glsl
+
+    ----- stderr -----
+    ");
+}
+
+#[test]
+fn picks_up_new_shaders() {
+    let space = Space::new();
+    write_shaders(&space, &["hypr/shaders/warm.glsl"]);
+    let ls = || {
+        let output = space.hyprshade_cmd().arg("ls").output().unwrap();
+        String::from_utf8(output.stdout).unwrap()
+    };
+    assert!(ls().contains("warm\n"));
+
+    write_shaders(&space, &["hypr/shaders/sub/cool.glsl"]);
+    assert!(ls().contains("cool\n"));
+
+    fs::remove_file(space.home().join(".config/hypr/shaders/warm.glsl")).unwrap();
+    assert!(!ls().contains("warm\n"));
+}
+
+#[test]
+fn no_cache() {
+    let space = Space::new();
+    write_shaders(&space, &["hypr/shaders/warm.glsl"]);
+
+    hyprshade_cmd_snapshot!(space.hyprshade_cmd().args(["ls", "--no-cache"]), @"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    blue-light-filter
+    color-adjust
+    color-filter
+    grayscale
+    invert-colors
+    lut
+    vibrance
+    warm
+
+    ----- stderr -----
+    ");
+}
@@ -1,9 +1,13 @@
+use std::{
+    fs::{self, File},
+    time::SystemTime,
+};
+
 use insta::assert_snapshot;
-use proc_macros::hyprland_test;
 
 use crate::common::{CommandExt, Space, hyprshade_cmd_snapshot};
 
-#[hyprland_test]
+#[test]
 fn builtin_shader() {
     let space = Space::new();
     let _stash = space.stash_runtime_shader("vibrance");
@@ -20,7 +24,69 @@ fn builtin_shader() {
     assert_snapshot!(space.read_runtime_shader("vibrance"));
 }
 
-#[hyprland_test]
+#[test]
+fn skips_when_already_on() {
+    let space = Space::new();
+    let _stash = space.stash_runtime_shader("vibrance");
+    let path = space.runtime_dir().join("vibrance.glsl");
+    let backdate = || {
+        File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(SystemTime::UNIX_EPOCH)
+            .unwrap();
+    };
+    let modified = || fs::metadata(&path).unwrap().modified().unwrap();
+
+    space.hyprshade_cmd().args(["on", "vibrance"]).run();
+    backdate();
+    space.hyprshade_cmd().args(["on", "vibrance"]).run();
+    assert_eq!(modified(), SystemTime::UNIX_EPOCH);
+
+    space
+        .hyprshade_cmd()
+        .args(["on", "vibrance", "--var", "balance.red=0.5"])
+        .run();
+    assert_ne!(modified(), SystemTime::UNIX_EPOCH);
+
+    backdate();
+    space
+        .hyprshade_cmd()
+        .args(["on", "vibrance", "--var", "balance.red=0.5", "--force"])
+        .run();
+    assert_ne!(modified(), SystemTime::UNIX_EPOCH);
+}
+
+#[test]
+fn reapplies_outdated() {
+    let space = Space::new();
+    let _stash = space.stash_runtime_shader("vibrance");
+    let rendered = || {
+        let output = space
+            .hyprshade_cmd()
+            .args(["current", "--long"])
+            .output()
+            .unwrap();
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        stdout
+            .lines()
+            .find_map(|line| line.strip_prefix("rendered: "))
+            .map(ToOwned::to_owned)
+    };
+
+    space.hyprshade_cmd().args(["on", "vibrance"]).run();
+    assert_eq!(rendered().as_deref(), Some("up to date"));
+
+    let path = space.runtime_dir().join("vibrance.glsl");
+    fs::write(&path, "// edited\n").unwrap();
+    assert!(rendered().unwrap().starts_with("outdated"));
+
+    space.hyprshade_cmd().args(["on", "vibrance"]).run();
+    assert_eq!(rendered().as_deref(), Some("up to date"));
+}
+
+#[test]
 fn path_shader() {
     let space = Space::new();
     space.hyprshade_cmd().arg("off").run();
@@ -35,6 +101,161 @@ fn path_shader() {
     "###);
 }
 
+#[test]
+fn lut_relative_path() {
+    let space = Space::new();
+    let _stash = space.stash_runtime_shader("lut");
+    let mut cube = String::from("LUT_3D_SIZE 2\n");
+    for [r, g, b] in [
+        [0, 0, 0],
+        [1, 0, 0],
+        [0, 1, 0],
+        [1, 1, 0],
+        [0, 0, 1],
+        [1, 0, 1],
+        [0, 1, 1],
+        [1, 1, 1],
+    ] {
+        cube += &format!("{r} {g} {b}\n");
+    }
+    fs::write(space.working_dir().join("identity.cube"), cube).unwrap();
+    space
+        .hyprshade_cmd()
+        .args(["on", "lut", "--var", "lut=identity.cube"])
+        .run();
+
+    // Rendered again from a directory the path isn't relative to
+    space
+        .hyprshade_cmd()
+        .current_dir(space.home())
+        .args(["adjust", "strength", "-0.5"])
+        .run();
+    assert!(
+        space
+            .read_runtime_shader("lut")
+            .contains("const float Strength = float(0.5);")
+    );
+}
+
+mod timed {
+
+    use crate::common::{CommandExt, Space, hyprshade_cmd_snapshot};
+
+    #[test]
+    fn reverts_to_previous() {
+        let mut space = Space::new();
+        space.with_time("12:00:00");
+        let _timed = space.stash_runtime_state("timed.json");
+        let _shaders = space.stash_runtime_shaders(["vibrance", "grayscale"]);
+
+        space.hyprshade_cmd().args(["on", "vibrance"]).run();
+        space
+            .hyprshade_cmd()
+            .args(["on", "grayscale", "--for", "20m"])
+            .run();
+        assert_eq!(space.current_shader().as_deref(), Some("grayscale"));
+        let calls = space.systemd_calls();
+        assert!(calls.contains("--on-active=1200s"), "{calls}");
+        assert!(calls.trim_end().ends_with(" revert"), "{calls}");
+
+        let output = space
+            .hyprshade_cmd()
+            .args(["current", "--long"])
+            .output()
+            .unwrap();
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        assert!(stdout.contains(" 12:20:00 to vibrance\n"), "{stdout}");
+
+        let output = space.hyprshade_cmd().arg("current").output().unwrap();
+        assert_eq!(String::from_utf8(output.stdout).unwrap(), "grayscale\n");
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        assert!(stderr.contains(" 12:20:00 to vibrance"), "{stderr}");
+
+        // Not time yet
+        space.with_time("12:10:00");
+        space.hyprshade_cmd().arg("revert").run();
+        assert_eq!(space.current_shader().as_deref(), Some("grayscale"));
+
+        space.with_time("12:20:00");
+        space.hyprshade_cmd().arg("revert").run();
+        assert_eq!(space.current_shader().as_deref(), Some("vibrance"));
+        assert!(!space.runtime_dir().join("timed.json").exists());
+    }
+
+    #[test]
+    fn reverts_to_schedule() {
+        let mut space = Space::new();
+        space.with_time("12:00:00").with_config(
+            r#"
+            [[shader]]
+            name = "vibrance"
+            default = true
+            "#,
+        );
+        let _timed = space.stash_runtime_state("timed.json");
+        let _inhibit = space.stash_runtime_state("inhibit.json");
+        let _shaders = space.stash_runtime_shaders(["vibrance", "grayscale"]);
+        space.hyprshade_cmd().arg("off").run();
+
+        space
+            .hyprshade_cmd()
+            .args(["on", "grayscale", "--for", "1h", "--then", "schedule"])
+            .run();
+        // The schedule waits for the timed shader
+        space.hyprshade_cmd().arg("auto").run();
+        assert_eq!(space.current_shader().as_deref(), Some("grayscale"));
+
+        space.with_time("13:00:00");
+        space.hyprshade_cmd().arg("revert").run();
+        assert_eq!(space.current_shader().as_deref(), Some("vibrance"));
+    }
+
+    #[test]
+    fn off_cancels() {
+        let mut space = Space::new();
+        space.with_time("12:00:00");
+        let _timed = space.stash_runtime_state("timed.json");
+        let _shader = space.stash_runtime_shader("grayscale");
+        space.hyprshade_cmd().arg("off").run();
+
+        space
+            .hyprshade_cmd()
+            .args(["on", "grayscale", "--for", "20m"])
+            .run();
+        space.hyprshade_cmd().arg("off").run();
+
+        assert!(!space.runtime_dir().join("timed.json").exists());
+        assert!(
+            space
+                .systemd_calls()
+                .ends_with("systemctl --user stop hyprshade-revert.timer\n")
+        );
+
+        space.with_time("12:20:00");
+        space.hyprshade_cmd().arg("revert").run();
+        assert_eq!(space.current_shader(), None);
+    }
+
+    #[test]
+    fn then_requires_for() {
+        let space = Space::new();
+
+        hyprshade_cmd_snapshot!(space.hyprshade_cmd().args(["on", "grayscale", "--then", "schedule"]), @"
+        success: false
+        exit_code: 2
+        ----- stdout -----
+
+        ----- stderr -----
+        error: the following required arguments were not provided:
+          --for <DURATION>
+
+        Usage: hyprshade on --for <DURATION> --then <THEN> <SHADER>
+
+        For more information, try '--help'.
+        ");
+    }
+}
+
 mod error {
     use crate::common::{Space, hyprshade_cmd_snapshot};
 
@@ -55,6 +276,43 @@ mod error {
         "###);
     }
 
+    #[test]
+    fn suggests_similar_names() {
+        let space = Space::new();
+        hyprshade_cmd_snapshot!(space.hyprshade_cmd().arg("on").args(["blue-light"]), @r#"
+        success: false
+        exit_code: 1
+        ----- stdout -----
+
+        ----- stderr -----
+        Error: 
+           0: [91mshader named "blue-light" not found[0m
+
+        Location:
+           [LOCATION]
+
+        [96mSuggestion[0m: Did you mean "blue-light-filter"?
+        "#);
+    }
+
+    #[test]
+    fn fails_baking_lut_without_file() {
+        let space = Space::new();
+        hyprshade_cmd_snapshot!(space.hyprshade_cmd().arg("on").args(["lut"]), @"
+        success: false
+        exit_code: 1
+        ----- stdout -----
+
+        ----- stderr -----
+        Error: 
+           0: [91mbaking data for lut[0m
+           1: [91mno LUT file given (use --var lut=PATH)[0m
+
+        Location:
+           [LOCATION]
+        ");
+    }
+
     #[test]
     fn fails_merging_var() {
         let space = Space::new();
@@ -0,0 +1,62 @@
+use crate::common::{CommandExt, Space, hyprshade_cmd_snapshot};
+
+/// What Hyprland does to the screen shader when reloading its configuration
+fn reload_hyprland_config(space: &Space) {
+    space
+        .hyprctl_cmd()
+        .args(["keyword", "decoration:screen_shader", "[[EMPTY]]"])
+        .run();
+}
+
+#[test]
+fn restores_last_shader() {
+    let space = Space::new();
+    let _last = space.stash_runtime_state("last.json");
+    let _shader = space.stash_runtime_shader("vibrance");
+
+    space
+        .hyprshade_cmd()
+        .args(["on", "vibrance", "--var", "balance.red=0.5"])
+        .run();
+    let rendered = space.read_runtime_shader("vibrance");
+    reload_hyprland_config(&space);
+    assert_eq!(space.current_shader(), None);
+
+    space.hyprshade_cmd().arg("restore").run();
+    assert_eq!(space.current_shader().as_deref(), Some("vibrance"));
+    assert_eq!(space.read_runtime_shader("vibrance"), rendered);
+}
+
+#[test]
+fn keeps_shader_off() {
+    let space = Space::new();
+    let _last = space.stash_runtime_state("last.json");
+    let _shader = space.stash_runtime_shader("vibrance");
+
+    space.hyprshade_cmd().args(["on", "vibrance"]).run();
+    space.hyprshade_cmd().arg("off").run();
+    reload_hyprland_config(&space);
+
+    space.hyprshade_cmd().arg("restore").run();
+    assert_eq!(space.current_shader(), None);
+}
+
+#[test]
+fn watch_fails_outside_hyprland() {
+    let space = Space::new();
+
+    hyprshade_cmd_snapshot!(space.hyprshade_cmd().args(["restore", "--watch"]).env_remove("HYPRLAND_INSTANCE_SIGNATURE"), @"
+    success: false
+    exit_code: 1
+    ----- stdout -----
+
+    ----- stderr -----
+    Error: 
+       0: [91mHYPRLAND_INSTANCE_SIGNATURE is not set[0m
+
+    Location:
+       [LOCATION]
+
+    [96mSuggestion[0m: Make sure hyprshade is running inside of Hyprland, or pass --instance
+    ");
+}
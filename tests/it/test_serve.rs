@@ -0,0 +1,251 @@
+use std::{
+    fs,
+    io::{BufRead, BufReader, Write},
+    os::unix::net::{UnixListener, UnixStream},
+    path::PathBuf,
+    process::{Child, Stdio},
+    thread,
+    time::{Duration, Instant},
+};
+
+use serde_json::{Value, json};
+
+use crate::common::{CommandExt, Space};
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// `hyprshade serve` running in a space, stopped on drop
+struct Server {
+    child: Child,
+    socket: PathBuf,
+}
+
+impl Server {
+    #[track_caller]
+    fn start(space: &Space, shaders_dir: Option<PathBuf>) -> Self {
+        let mut cmd = space.hyprshade_cmd();
+        cmd.arg("serve").stdout(Stdio::null()).stderr(Stdio::null());
+        if let Some(dir) = shaders_dir {
+            cmd.env("HYPRSHADE_SHADERS_DIR", dir);
+        }
+        let child = cmd.spawn().unwrap();
+
+        let socket = space.runtime_dir().join("control.sock");
+        let start = Instant::now();
+        while UnixStream::connect(&socket).is_err() {
+            assert!(
+                start.elapsed() < TIMEOUT,
+                "{socket:?} was never listened on"
+            );
+            thread::sleep(Duration::from_millis(20));
+        }
+
+        Self { child, socket }
+    }
+
+    /// Sends each of `requests` on one connection, and returns the responses.
+    fn send(&self, requests: &[Value]) -> Vec<Value> {
+        let stream = UnixStream::connect(&self.socket).unwrap();
+        for request in requests {
+            writeln!(&stream, "{request}").unwrap();
+        }
+        BufReader::new(&stream)
+            .lines()
+            .take(requests.len())
+            .map(|line| serde_json::from_str(&line.unwrap()).unwrap())
+            .collect()
+    }
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        self.child.kill().ok();
+        self.child.wait().ok();
+    }
+}
+
+#[test]
+fn forwards_subcommands() {
+    let mut space = Space::new();
+    space.with_any_time();
+    let shaders_dir = space.working_dir().join("served");
+    fs::create_dir(&shaders_dir).unwrap();
+    fs::copy(space.fixture_simple(), shaders_dir.join("served.glsl")).unwrap();
+    let _server = Server::start(&space, Some(shaders_dir.clone()));
+
+    space
+        .hyprshade_cmd()
+        .env("HYPRSHADE_SHADERS_DIR", &shaders_dir)
+        .args(["on", "served"])
+        .run();
+    assert_eq!(space.current_shader().as_deref(), Some("served"));
+    let output = space
+        .hyprshade_cmd()
+        .env("HYPRSHADE_SHADERS_DIR", &shaders_dir)
+        .arg("current")
+        .run();
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "served\n");
+
+    // Run with the client's shader directories, which don't include the server's
+    let output = space
+        .hyprshade_cmd()
+        .args(["on", "served"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+
+    // Not forwarded
+    let output = space
+        .hyprshade_cmd()
+        .args(["which", "served"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+}
+
+#[test]
+fn reports_errors() {
+    let mut space = Space::new();
+    space.with_any_time();
+    let _server = Server::start(&space, None);
+
+    let output = space
+        .hyprshade_cmd()
+        .args(["on", "invalid"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains(r#"shader named "invalid" not found"#),
+        "{stderr}"
+    );
+}
+
+#[test]
+fn forwards_warnings() {
+    let mut space = Space::new();
+    space.with_any_time();
+    let shaders_dir = space.home().join(".config/hypr/shaders");
+    fs::create_dir_all(&shaders_dir).unwrap();
+    for file_name in ["vibrance.glsl", "vibrance.frag"] {
+        fs::copy(space.fixture_simple(), shaders_dir.join(file_name)).unwrap();
+    }
+    let server = Server::start(&space, None);
+
+    let responses = server.send(&[json!({"args": ["on", "vibrance"]})]);
+    assert_eq!(responses[0]["status"], "ok", "{}", responses[0]);
+    let log = responses[0]["log"].as_str().unwrap();
+    assert!(log.contains("More than one shader named"), "{log}");
+
+    let output = space.hyprshade_cmd().args(["on", "vibrance"]).run();
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("More than one shader named"), "{stderr}");
+}
+
+#[test]
+fn protocol() {
+    let mut space = Space::new();
+    space.with_any_time();
+    let server = Server::start(&space, None);
+
+    let responses = server.send(&[
+        json!({"args": ["on", "vibrance"]}),
+        json!({"args": ["current"]}),
+        json!({"args": ["ls"]}),
+        json!({"argv": []}),
+        json!({"args": ["current"], "env": {}}),
+    ]);
+    assert_eq!(responses[0], json!({"status": "ok", "output": ""}));
+    assert_eq!(
+        responses[1],
+        json!({"status": "ok", "output": "vibrance\n"})
+    );
+    assert_eq!(responses[2]["status"], "error");
+    assert_eq!(
+        responses[2]["message"],
+        "hyprshade serve doesn't run this subcommand"
+    );
+    assert_eq!(responses[3]["status"], "error");
+    assert_eq!(responses[3]["message"], "invalid request");
+    assert_eq!(responses[4]["status"], "declined");
+}
+
+#[test]
+fn relative_paths() {
+    let mut space = Space::new();
+    space.with_any_time();
+    let server = Server::start(&space, None);
+    fs::copy(
+        space.fixture_simple(),
+        space.working_dir().join("simple.glsl"),
+    )
+    .unwrap();
+
+    let other_dir = space.working_dir().join("other");
+    fs::create_dir(&other_dir).unwrap();
+    fs::copy(space.fixture_simple(), other_dir.join("other.glsl")).unwrap();
+
+    let responses = server.send(&[
+        json!({
+            "args": ["on", "./other.glsl"],
+            "cwd": other_dir,
+        }),
+        // Relative to where the server was started, not to the previous request
+        json!({"args": ["on", "./other.glsl"]}),
+        json!({"args": ["on", "./simple.glsl"]}),
+    ]);
+    assert_eq!(responses[0]["status"], "ok", "{}", responses[0]);
+    assert_eq!(responses[1]["status"], "error", "{}", responses[1]);
+    assert_eq!(responses[2]["status"], "ok", "{}", responses[2]);
+    assert_eq!(space.current_shader().as_deref(), Some("simple"));
+}
+
+#[test]
+fn picks_up_changes() {
+    let mut space = Space::new();
+    space.with_time("12:00:00");
+    let _server = Server::start(&space, None);
+
+    // Created after the server started
+    let shaders_dir = space.home().join(".config/hypr/shaders");
+    fs::create_dir_all(&shaders_dir).unwrap();
+    fs::copy(space.fixture_simple(), shaders_dir.join("new.glsl")).unwrap();
+    space.hyprshade_cmd().args(["on", "new"]).run();
+    assert_eq!(space.current_shader().as_deref(), Some("new"));
+
+    space.with_config(
+        r#"
+            [[shader]]
+            name = "grayscale"
+            default = true
+        "#,
+    );
+    space.hyprshade_cmd().arg("auto").run();
+    assert_eq!(space.current_shader().as_deref(), Some("grayscale"));
+
+    space.with_config(
+        r#"
+            [[shader]]
+            name = "vibrance"
+            default = true
+        "#,
+    );
+    space.hyprshade_cmd().arg("auto").run();
+    assert_eq!(space.current_shader().as_deref(), Some("vibrance"));
+}
+
+#[test]
+fn falls_back_without_server() {
+    let mut space = Space::new();
+    space.with_any_time();
+    // Left behind by a server that is no longer running
+    drop(UnixListener::bind(space.runtime_dir().join("control.sock")).unwrap());
+
+    space.hyprshade_cmd().args(["on", "vibrance"]).run();
+    assert_eq!(space.current_shader().as_deref(), Some("vibrance"));
+
+    let _server = Server::start(&space, None);
+    space.hyprshade_cmd().arg("off").run();
+    assert_eq!(space.current_shader(), None);
+}
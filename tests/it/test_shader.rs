@@ -0,0 +1,441 @@
+use std::{fs, path::PathBuf};
+
+use crate::common::{CommandExt, Space, hyprshade_cmd_snapshot};
+
+const SHADER: &str = "void main() {}\n";
+
+fn make_pack_dir(space: &Space, name: &str, files: &[&str]) -> PathBuf {
+    let dir = space.working_dir().join(name);
+    for file in files {
+        let path = dir.join(file);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, SHADER).unwrap();
+    }
+    dir
+}
+
+#[test]
+fn add_directory() {
+    let space = Space::new();
+    let source = make_pack_dir(
+        &space,
+        "team",
+        &["warm.glsl", "sub/crt.glsl.mustache", "README.md"],
+    );
+
+    hyprshade_cmd_snapshot!(space.hyprshade_cmd().args(["shader", "add"]).arg(&source), @"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+
+    ----- stderr -----
+    ");
+    hyprshade_cmd_snapshot!(space.hyprshade_cmd().args(["shader", "list"]), @"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    team
+      crt
+      warm
+
+    ----- stderr -----
+    ");
+
+    let pack_dir = space.home().join(".config/hyprshade/shaders/team");
+    assert!(pack_dir.join("sub/crt.glsl.mustache").is_file());
+    assert!(!pack_dir.join("README.md").exists());
+}
+
+#[test]
+fn add_tarball_with_manifest() {
+    let space = Space::new();
+    let source = make_pack_dir(&space, "team-shaders", &["warm.glsl", "unlisted.glsl"]);
+    fs::write(
+        source.join("hyprshade-pack.toml"),
+        "name = \"team\"\nversion = \"1.2.0\"\nshaders = [\"warm.glsl\"]\n",
+    )
+    .unwrap();
+
+    let tarball = space.working_dir().join("team-1.2.0.tar.gz");
+    let encoder = flate2::write::GzEncoder::new(
+        fs::File::create(&tarball).unwrap(),
+        flate2::Compression::default(),
+    );
+    let mut builder = tar::Builder::new(encoder);
+    builder.append_dir_all("team-shaders", &source).unwrap();
+    builder.into_inner().unwrap().finish().unwrap();
+
+    space
+        .hyprshade_cmd()
+        .args(["shader", "add"])
+        .arg(&tarball)
+        .run();
+
+    hyprshade_cmd_snapshot!(space.hyprshade_cmd().args(["shader", "list"]), @"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    team 1.2.0
+      warm
+
+    ----- stderr -----
+    ");
+}
+
+#[test]
+fn add_copies_metadata_sidecars() {
+    let space = Space::new();
+    let source = make_pack_dir(&space, "team", &["warm.glsl.mustache", "notes.toml"]);
+    fs::write(
+        source.join("warm.toml"),
+        "[variables.strength]\ndefault = 0.5\n",
+    )
+    .unwrap();
+
+    space
+        .hyprshade_cmd()
+        .args(["shader", "add"])
+        .arg(&source)
+        .run();
+
+    let pack_dir = space.home().join(".config/hyprshade/shaders/team");
+    assert!(pack_dir.join("warm.toml").is_file());
+    assert!(!pack_dir.join("notes.toml").exists());
+}
+
+#[test]
+fn add_tarball_ignores_links() {
+    let space = Space::new();
+    let source = make_pack_dir(&space, "team", &["warm.glsl"]);
+    fs::write(
+        source.join("hyprshade-pack.toml"),
+        "name = \"team\"\nshaders = [\"cool.glsl\"]\n",
+    )
+    .unwrap();
+    let secret = space.working_dir().join("secret.txt");
+    fs::write(&secret, "secret").unwrap();
+
+    let tarball = space.working_dir().join("team.tar");
+    let mut builder = tar::Builder::new(fs::File::create(&tarball).unwrap());
+    builder.append_dir_all("team", &source).unwrap();
+    let mut header = tar::Header::new_gnu();
+    header.set_entry_type(tar::EntryType::Symlink);
+    header.set_size(0);
+    builder
+        .append_link(&mut header, "team/cool.glsl", &secret)
+        .unwrap();
+    builder.into_inner().unwrap();
+
+    let output = space
+        .hyprshade_cmd()
+        .args(["shader", "add"])
+        .arg(&tarball)
+        .output()
+        .unwrap();
+
+    assert!(!output.status.success());
+    assert!(!space.home().join(".config/hyprshade/shaders/team").exists());
+}
+
+#[test]
+fn add_force_replaces_pack() {
+    let space = Space::new();
+    let source = make_pack_dir(&space, "team", &["warm.glsl"]);
+    space
+        .hyprshade_cmd()
+        .args(["shader", "add"])
+        .arg(&source)
+        .run();
+
+    fs::remove_file(source.join("warm.glsl")).unwrap();
+    fs::write(source.join("cool.glsl"), SHADER).unwrap();
+    space
+        .hyprshade_cmd()
+        .args(["shader", "add", "--force"])
+        .arg(&source)
+        .run();
+
+    hyprshade_cmd_snapshot!(space.hyprshade_cmd().args(["shader", "list"]), @"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    team
+      cool
+
+    ----- stderr -----
+    ");
+}
+
+#[test]
+fn add_force_warns_about_collisions() {
+    let space = Space::new();
+    let source = make_pack_dir(&space, "team", &["vibrance.glsl"]);
+
+    hyprshade_cmd_snapshot!(space.hyprshade_cmd().args(["shader", "add", "--force"]).arg(&source), @r#"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+
+    ----- stderr -----
+     WARN "vibrance" shadows the builtin shader
+    "#);
+}
+
+#[test]
+fn remove() {
+    let space = Space::new();
+    let source = make_pack_dir(&space, "team", &["warm.glsl"]);
+    space
+        .hyprshade_cmd()
+        .args(["shader", "add"])
+        .arg(&source)
+        .run();
+
+    hyprshade_cmd_snapshot!(space.hyprshade_cmd().args(["shader", "remove", "team"]), @"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+
+    ----- stderr -----
+    ");
+
+    assert!(!space.home().join(".config/hyprshade/shaders/team").exists());
+}
+
+#[test]
+fn ls_includes_packs_and_builtins() {
+    let space = Space::new();
+    let source = make_pack_dir(&space, "team", &["warm.glsl"]);
+    space
+        .hyprshade_cmd()
+        .args(["shader", "add"])
+        .arg(&source)
+        .run();
+
+    hyprshade_cmd_snapshot!(space.hyprshade_cmd().arg("ls"), @"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    blue-light-filter
+    color-adjust
+    color-filter
+    grayscale
+    invert-colors
+    lut
+    vibrance
+    warm
+
+    ----- stderr -----
+    ");
+}
+
+#[test]
+fn installed_shader_is_resolvable() {
+    let space = Space::new();
+    let source = make_pack_dir(&space, "team", &["warm.glsl"]);
+    space
+        .hyprshade_cmd()
+        .args(["shader", "add"])
+        .arg(&source)
+        .run();
+
+    space.hyprshade_cmd().args(["on", "warm"]).run();
+
+    assert_eq!(space.current_shader().as_deref(), Some("warm"));
+}
+
+#[test]
+fn eject() {
+    let space = Space::new();
+
+    hyprshade_cmd_snapshot!(space.hyprshade_cmd().args(["shader", "eject", "vibrance"]), @r#"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    [HYPRSHADE_HOME]/.config/hypr/shaders/vibrance.glsl.mustache
+
+    ----- stderr -----
+     WARN "[HYPRSHADE_HOME]/.config/hypr/shaders/vibrance.glsl.mustache" now shadows the builtin shader "vibrance" when turned on by name
+    "#);
+
+    let shaders_dir = space.home().join(".config/hypr/shaders");
+    assert!(shaders_dir.join("vibrance.glsl.mustache").is_file());
+    let metadata = fs::read_to_string(shaders_dir.join("vibrance.toml")).unwrap();
+    assert!(metadata.contains("[variables.strength]"));
+}
+
+#[test]
+fn eject_as() {
+    let space = Space::new();
+
+    hyprshade_cmd_snapshot!(space.hyprshade_cmd().args(["shader", "eject", "vibrance", "--as", "my-vibrance"]), @"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    [HYPRSHADE_HOME]/.config/hypr/shaders/my-vibrance.glsl.mustache
+
+    ----- stderr -----
+    ");
+}
+
+#[test]
+fn ejected_shader_uses_metadata_defaults() {
+    let space = Space::new();
+    let _stash = space.stash_runtime_shaders(["vibrance", "my-vibrance"]);
+    space
+        .hyprshade_cmd()
+        .args(["shader", "eject", "vibrance", "--as", "my-vibrance"])
+        .run();
+
+    space.hyprshade_cmd().args(["on", "vibrance"]).run();
+    let builtin = space.read_runtime_shader("vibrance");
+    space.hyprshade_cmd().args(["on", "my-vibrance"]).run();
+
+    assert_eq!(space.read_runtime_shader("my-vibrance"), builtin);
+}
+
+mod error {
+    use std::fs;
+
+    use super::make_pack_dir;
+    use crate::common::{CommandExt, Space, hyprshade_cmd_snapshot};
+
+    #[test]
+    fn fails_with_collisions() {
+        let space = Space::new();
+        let shaders_dir = space.home().join(".config/hypr/shaders");
+        fs::create_dir_all(&shaders_dir).unwrap();
+        fs::write(shaders_dir.join("warm.glsl"), super::SHADER).unwrap();
+        let source = make_pack_dir(&space, "team", &["warm.glsl", "vibrance.glsl"]);
+
+        hyprshade_cmd_snapshot!(space.hyprshade_cmd().args(["shader", "add"]).arg(&source), @r#"
+        success: false
+        exit_code: 1
+        ----- stdout -----
+
+        ----- stderr -----
+        Error: 
+           0: [91mshaders in pack "team" collide with existing shaders[0m
+
+        Location:
+           [LOCATION]
+
+        Collisions:
+           "vibrance" shadows the builtin shader
+           "warm" collides with "[HYPRSHADE_HOME]/.config/hypr/shaders/warm.glsl"
+
+        [96mSuggestion[0m: Rename the shaders in the pack (listed in hyprshade-pack.toml), or use --force to install anyway
+        "#);
+
+        assert!(!space.home().join(".config/hyprshade/shaders/team").exists());
+    }
+
+    #[test]
+    fn fails_when_already_installed() {
+        let space = Space::new();
+        let source = make_pack_dir(&space, "team", &["warm.glsl"]);
+        space
+            .hyprshade_cmd()
+            .args(["shader", "add"])
+            .arg(&source)
+            .run();
+
+        hyprshade_cmd_snapshot!(space.hyprshade_cmd().args(["shader", "add"]).arg(&source), @r#"
+        success: false
+        exit_code: 1
+        ----- stdout -----
+
+        ----- stderr -----
+        Error: 
+           0: [91mpack "team" is already installed[0m
+
+        Location:
+           [LOCATION]
+
+        [96mSuggestion[0m: Use --force to replace it, or `hyprshade shader remove` it first
+        "#);
+    }
+
+    #[test]
+    fn fails_with_duplicate_names() {
+        let space = Space::new();
+        let source = make_pack_dir(&space, "team", &["warm.glsl", "sub/warm.frag"]);
+
+        hyprshade_cmd_snapshot!(space.hyprshade_cmd().args(["shader", "add"]).arg(&source), @r#"
+        success: false
+        exit_code: 1
+        ----- stdout -----
+
+        ----- stderr -----
+        Error: 
+           0: [91mmore than one shader is named "warm": "sub/warm.frag" and "warm.glsl"[0m
+
+        Location:
+           [LOCATION]
+        "#);
+    }
+
+    #[test]
+    fn fails_ejecting_over_existing_file() {
+        let space = Space::new();
+        space
+            .hyprshade_cmd()
+            .args(["shader", "eject", "vibrance"])
+            .run();
+
+        hyprshade_cmd_snapshot!(space.hyprshade_cmd().args(["shader", "eject", "vibrance"]), @r#"
+        success: false
+        exit_code: 1
+        ----- stdout -----
+
+        ----- stderr -----
+        Error: 
+           0: [91m"[HYPRSHADE_HOME]/.config/hypr/shaders/vibrance.glsl.mustache" already exists[0m
+
+        Location:
+           [LOCATION]
+
+        [96mSuggestion[0m: Use --force to overwrite it
+        "#);
+    }
+
+    #[test]
+    fn fails_ejecting_baked_shader() {
+        let space = Space::new();
+
+        hyprshade_cmd_snapshot!(space.hyprshade_cmd().args(["shader", "eject", "lut"]), @r#"
+        success: false
+        exit_code: 1
+        ----- stdout -----
+
+        ----- stderr -----
+        Error: 
+           0: [91mbuiltin shader "lut" can't be ejected[0m
+
+        Location:
+           [LOCATION]
+
+        [96mNote[0m: It is generated from data computed when it is turned on
+        "#);
+    }
+
+    #[test]
+    fn fails_removing_unknown_pack() {
+        let space = Space::new();
+
+        hyprshade_cmd_snapshot!(space.hyprshade_cmd().args(["shader", "remove", "team"]), @r#"
+        success: false
+        exit_code: 1
+        ----- stdout -----
+
+        ----- stderr -----
+        Error: 
+           0: [91mpack "team" is not installed[0m
+
+        Location:
+           [LOCATION]
+
+        [96mSuggestion[0m: Use `hyprshade shader list` to list installed packs
+        "#);
+    }
+}
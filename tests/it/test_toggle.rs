@@ -1,5 +1,3 @@
-use proc_macros::hyprland_test;
-
 use crate::common::{CommandExt, Space, hyprshade_cmd_snapshot};
 
 #[test]
@@ -24,7 +22,7 @@ fn empty_arg_fails_without_config() {
     ");
 }
 
-#[hyprland_test]
+#[test]
 fn empty_arg_fails_resolving_default_shader() {
     let mut space = Space::new();
     space.with_any_time().with_config(
@@ -58,7 +56,7 @@ fn empty_arg_fails_resolving_default_shader() {
     "#);
 }
 
-#[hyprland_test]
+#[test]
 fn empty_arg_fails_resolving_scheduled_shader() {
     let mut space = Space::new();
     space.with_time("01:00:00").with_config(
@@ -95,7 +93,7 @@ fn empty_arg_fails_resolving_scheduled_shader() {
     "#);
 }
 
-#[hyprland_test]
+#[test]
 fn empty_arg_with_active_shader_turns_off() {
     let mut space = Space::new();
     space.with_any_time().with_config(r#""#);
@@ -114,7 +112,7 @@ fn empty_arg_with_active_shader_turns_off() {
     assert!(space.current_shader().is_none());
 }
 
-#[hyprland_test]
+#[test]
 fn empty_arg_with_no_active_shader_turns_on_default() {
     let mut space = Space::new();
     space.with_any_time().with_config(
@@ -186,7 +184,7 @@ fn fallback_default_fails_without_default_shader() {
     ");
 }
 
-#[hyprland_test]
+#[test]
 fn fallback_default_fails_resolving_shader() {
     let mut space = Space::new();
     space.with_any_time().with_config(
@@ -221,7 +219,7 @@ fn fallback_default_fails_resolving_shader() {
     "#);
 }
 
-#[hyprland_test]
+#[test]
 fn fallback_default_with_active_shader_turns_on_default() {
     let mut space = Space::new();
     space.with_any_time().with_config(
@@ -246,7 +244,7 @@ fn fallback_default_with_active_shader_turns_on_default() {
     assert_eq!(space.current_shader().as_deref(), Some("color-filter"));
 }
 
-#[hyprland_test]
+#[test]
 fn fallback_default_with_default_turns_on_positional_arg() {
     let mut space = Space::new();
     space.with_any_time().with_config(
@@ -293,7 +291,7 @@ fn fallback_auto_fails_without_config() {
     ");
 }
 
-#[hyprland_test]
+#[test]
 fn fallback_auto_fails_resolving_scheduled_shader() {
     let mut space = Space::new();
     space.with_time("01:00:00").with_config(
@@ -330,7 +328,7 @@ fn fallback_auto_fails_resolving_scheduled_shader() {
     "#);
 }
 
-#[hyprland_test]
+#[test]
 fn fallback_auto_fails_resolving_default_shader() {
     let mut space = Space::new();
     space.with_time("01:00:00").with_config(
@@ -370,7 +368,7 @@ fn fallback_auto_fails_resolving_default_shader() {
     "#);
 }
 
-#[hyprland_test]
+#[test]
 fn fallback_auto_success() {
     #[derive(Clone, Copy, Debug)]
     enum TestVariant {
@@ -739,6 +737,30 @@ fn fallback_flags_are_mutually_exclusive() {
     ");
 }
 
+#[test]
+fn concurrent_toggles_do_not_interleave() {
+    let mut space = Space::new();
+    space.with_any_time();
+    let _stash = space.stash_runtime_shader("vibrance");
+    space.hyprshade_cmd().arg("off").run();
+
+    let children: Vec<_> = (0..8)
+        .map(|_| {
+            space
+                .hyprshade_cmd()
+                .args(["toggle", "vibrance"])
+                .spawn()
+                .unwrap()
+        })
+        .collect();
+    for mut child in children {
+        assert!(child.wait().unwrap().success());
+    }
+
+    // Every toggle saw the result of the one before it
+    assert!(space.current_shader().is_none());
+}
+
 #[test]
 #[ignore = "already tested in test_on::error::fails_merging_var"]
 fn fails_merging_var() {
@@ -0,0 +1,134 @@
+use std::fs;
+
+use crate::common::{Space, hyprshade_cmd_snapshot};
+
+const SHADER: &str = "void main() {}\n";
+
+fn write_shaders(space: &Space, paths: &[&str]) {
+    for path in paths {
+        let path = space.home().join(".config").join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, SHADER).unwrap();
+    }
+}
+
+#[test]
+fn builtin() {
+    let space = Space::new();
+
+    hyprshade_cmd_snapshot!(space.hyprshade_cmd().args(["which", "vibrance"]), @"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    vibrance (builtin)
+
+    ----- stderr -----
+    ");
+}
+
+#[test]
+fn shadowing_and_ambiguity() {
+    let space = Space::new();
+    write_shaders(
+        &space,
+        &[
+            "hypr/shaders/sub/vibrance.glsl",
+            "hypr/shaders/vibrance.glsl.mustache",
+            "hypr/shaders/vibrance.glsl",
+            "hyprshade/shaders/vibrance.glsl",
+        ],
+    );
+
+    hyprshade_cmd_snapshot!(space.hyprshade_cmd().args(["which", "vibrance"]), @r#"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    [HYPRSHADE_HOME]/.config/hypr/shaders/vibrance.glsl
+      ambiguous with [HYPRSHADE_HOME]/.config/hypr/shaders/vibrance.glsl.mustache
+      ambiguous with [HYPRSHADE_HOME]/.config/hypr/shaders/sub/vibrance.glsl
+      shadows [HYPRSHADE_HOME]/.config/hyprshade/shaders/vibrance.glsl
+      shadows builtin shader "vibrance"
+
+    ----- stderr -----
+    "#);
+}
+
+#[test]
+fn resolving_ambiguous_name_warns() {
+    let space = Space::new();
+    let _stash = space.stash_runtime_shader("vibrance");
+    write_shaders(
+        &space,
+        &["hypr/shaders/vibrance.glsl", "hypr/shaders/vibrance.frag"],
+    );
+
+    hyprshade_cmd_snapshot!(space.hyprshade_cmd().args(["on", "vibrance"]), @r#"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+
+    ----- stderr -----
+     WARN More than one shader named "vibrance" in "[HYPRSHADE_HOME]/.config/hypr/shaders", using "vibrance.frag" over ["vibrance.glsl"]
+    "#);
+    assert_eq!(space.current_shader().as_deref(), Some("vibrance"));
+}
+
+mod error {
+    use crate::common::{Space, hyprshade_cmd_snapshot};
+
+    #[test]
+    fn fails_with_unknown_name() {
+        let space = Space::new();
+
+        hyprshade_cmd_snapshot!(space.hyprshade_cmd().args(["which", "invalid"]), @r#"
+        success: false
+        exit_code: 1
+        ----- stdout -----
+
+        ----- stderr -----
+        Error: 
+           0: [91mshader named "invalid" not found[0m
+
+        Location:
+           [LOCATION]
+
+        [96mSuggestion[0m: Use `hyprshade ls` to list shaders
+        "#);
+    }
+}
+
+#[test]
+fn shader_dirs_precedence() {
+    let mut space = Space::new();
+    space.with_config(
+        r#"
+        shader_dirs = ["extra"]
+        "#,
+    );
+    for dir in [
+        "env-a",
+        "env-b",
+        ".config/hyprshade/extra",
+        ".local/share/hyprshade/shaders",
+        "usr/share/hyprshade/shaders",
+    ] {
+        let path = space.home().join(dir).join("warm.glsl");
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, SHADER).unwrap();
+    }
+    let env_dirs =
+        std::env::join_paths([space.home().join("env-a"), space.home().join("env-b")]).unwrap();
+
+    hyprshade_cmd_snapshot!(space.hyprshade_cmd().args(["which", "warm"]).env("HYPRSHADE_SHADERS_DIR", &env_dirs), @"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    [HYPRSHADE_HOME]/env-a/warm.glsl
+      shadows [HYPRSHADE_HOME]/env-b/warm.glsl
+      shadows [HYPRSHADE_HOME]/.config/hyprshade/extra/warm.glsl
+      shadows [HYPRSHADE_HOME]/.local/share/hyprshade/shaders/warm.glsl
+      shadows [HYPRSHADE_HOME]/usr/share/hyprshade/shaders/warm.glsl
+
+    ----- stderr -----
+    ");
+}
@@ -1,4 +1,4 @@
-use hyprshade::__private::{Shader, ShaderInstance};
+use hyprshade::{Hyprland, Shader, ShaderInstance};
 
 pub struct RestoreShaderHook {
     saved_shader: Option<ShaderInstance>,
@@ -6,7 +6,7 @@ pub struct RestoreShaderHook {
 
 impl RestoreShaderHook {
     pub fn new() -> Self {
-        let saved_shader = Shader::current().unwrap_or_else(|err| {
+        let saved_shader = Shader::current(&Hyprland).unwrap_or_else(|err| {
             report_error(&err);
             None
         });
@@ -18,15 +18,19 @@ impl RestoreShaderHook {
         let Self { saved_shader } = self;
         eprintln!("Restoring shader: {saved_shader:?}");
         match saved_shader {
-            Some(shader) => shader.restore().unwrap_or_else(|err| report_error(&err)),
-            None => Shader::off().unwrap_or_else(|err| report_error(&err)),
+            Some(shader) => shader
+                .restore(&Hyprland)
+                .unwrap_or_else(|err| report_error(&err)),
+            None => Shader::off(&Hyprland).unwrap_or_else(|err| report_error(&err)),
         }
     }
 }
 
-fn report_error(err: &eyre::Report) {
+fn report_error(err: &dyn std::error::Error) {
     eprintln!("Error:");
-    for cause in err.chain() {
-        eprintln!("  {cause}");
+    let mut cause = Some(err);
+    while let Some(err) = cause {
+        eprintln!("  {err}");
+        cause = err.source();
     }
 }